@@ -6,6 +6,7 @@ pub mod paras_refundable {
     use ink::codegen::{EmitEvent, Env};
     use openbrush::{
         contracts::{
+            access_control::*,
             ownable::*,
             psp34::{
                 extensions::{enumerable::*, metadata::*},
@@ -20,11 +21,17 @@ pub mod paras_refundable {
 
     use psp34_extension_pkg::{
         impls::launchpad::{
-            types::{MilliSeconds, Percentage},
+            types::{
+                BlockTimestamp, MilliSeconds, Percentage, Shiden34Error, TimelockOperation,
+                MARKETPLACE, MAX_PENDING_OPERATIONS, MAX_REFUND_PERIOD, METADATA_ADMIN,
+                PHASE_ADMIN, STORAGE_VERSION, UPGRADER, WHITELIST_MANAGER,
+            },
             *,
         },
         traits::launchpad::*,
         traits::psp34_traits::*,
+        traits::royalty::*,
+        traits::timelock::*,
     };
 
     // Shiden34Contract contract storage
@@ -39,12 +46,44 @@ pub mod paras_refundable {
         metadata: metadata::Data,
         #[storage_field]
         launchpad: types::Data,
+        access: RolesData,
+    }
+
+    /// Access control added after the first deployment. Roles live in their own mappings, so
+    /// the wrapper writes nothing into the root cell and contracts deployed without it still decode
+    #[derive(Default, Debug)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct RolesData(access_control::Data);
+
+    impl scale::Encode for RolesData {
+        fn size_hint(&self) -> usize {
+            0
+        }
+
+        fn encode_to<O: scale::Output + ?Sized>(&self, _dest: &mut O) {}
+    }
+
+    impl scale::Decode for RolesData {
+        fn decode<I: scale::Input>(_input: &mut I) -> Result<Self, scale::Error> {
+            Ok(Self::default())
+        }
+    }
+
+    impl Storage<access_control::Data> for ParasRefundableContract {
+        fn get(&self) -> &access_control::Data {
+            &self.access.0
+        }
+
+        fn get_mut(&mut self) -> &mut access_control::Data {
+            &mut self.access.0
+        }
     }
 
     impl PSP34 for ParasRefundableContract {}
     impl PSP34Enumerable for ParasRefundableContract {}
     impl PSP34Metadata for ParasRefundableContract {}
     impl Ownable for ParasRefundableContract {}
+    impl AccessControl for ParasRefundableContract {}
 
     /// Event emitted when a token transfer occurs.
     #[ink(event)]
@@ -82,6 +121,103 @@ pub mod paras_refundable {
         refunded: Balance,
     }
 
+    #[ink(event)]
+    pub struct MilestoneApproved {
+        index: u32,
+        #[ink(topic)]
+        approver: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ProjectDefaulted {
+        #[ink(topic)]
+        proposal_id: Option<u32>,
+        refund_ratio: u128,
+    }
+
+    #[ink(event)]
+    pub struct Withdrawn {
+        #[ink(topic)]
+        payer: AccountId,
+        #[ink(topic)]
+        destination: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct AddressChangeProposed {
+        address_kind: u8,
+        #[ink(topic)]
+        proposer: AccountId,
+        #[ink(topic)]
+        new_address: AccountId,
+    }
+
+    /// Event emitted when a holder gives up the refund of a token.
+    #[ink(event)]
+    pub struct RefundWaived {
+        #[ink(topic)]
+        holder: AccountId,
+        token_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct AddressChanged {
+        address_kind: u8,
+        #[ink(topic)]
+        old_address: Option<AccountId>,
+        #[ink(topic)]
+        new_address: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OperationScheduled {
+        #[ink(topic)]
+        operation_id: u32,
+        operation: TimelockOperation,
+        #[ink(topic)]
+        proposer: AccountId,
+        ready_at: BlockTimestamp,
+    }
+
+    #[ink(event)]
+    pub struct OperationExecuted {
+        #[ink(topic)]
+        operation_id: u32,
+    }
+
+    #[ink(event)]
+    pub struct OperationCancelled {
+        #[ink(topic)]
+        operation_id: u32,
+    }
+
+    /// Event emitted when the collection metadata, or the metadata of a single token, is frozen.
+    #[ink(event)]
+    pub struct MetadataFrozen {
+        #[ink(topic)]
+        token_id: Option<u64>,
+    }
+
+    /// Event emitted once per attribute batch with the number of tokens updated and skipped.
+    #[ink(event)]
+    pub struct AttributesBatchSet {
+        updated: u32,
+        failed: u32,
+    }
+
+    #[ink(event)]
+    pub struct Upgraded {
+        #[ink(topic)]
+        code_hash: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct Migrated {
+        from_version: u32,
+        to_version: u32,
+    }
+
     impl ParasRefundableContract {
         #[ink(constructor)]
         pub fn new(
@@ -105,7 +241,12 @@ pub mod paras_refundable {
         ) -> Self {
             let mut instance = Self::default();
 
-            instance._init_with_owner(instance.env().caller());
+            let caller = instance.env().caller();
+            instance._init_with_owner(caller);
+            instance._init_with_admin(caller);
+            for role in [WHITELIST_MANAGER, METADATA_ADMIN, PHASE_ADMIN, UPGRADER] {
+                instance._setup_role(role, caller);
+            }
             let collection_id = instance.collection_id();
             instance._set_attribute(collection_id.clone(), String::from("name"), name);
             instance._set_attribute(collection_id.clone(), String::from("symbol"), symbol);
@@ -126,41 +267,184 @@ pub mod paras_refundable {
 
             // validation
             assert_eq!(refund_periods.len(), refund_shares.len());
+            for refund_period in &refund_periods {
+                assert!(refund_period <= &MAX_REFUND_PERIOD);
+            }
             for refund_share in &refund_shares {
                 assert!(refund_share < &100);
             }
             assert!(launchpad_fee < 100);
 
-
             instance.launchpad.refund_periods = refund_periods;
             instance.launchpad.refund_shares = refund_shares;
             instance.launchpad.refund_address = Some(refund_address);
             instance.launchpad.total_sales = 0;
-            instance.launchpad.withdrawn_sales_launchpad = 0;
-            instance.launchpad.withdrawn_sales_project = 0;
             instance.launchpad.launchpad_fee = launchpad_fee;
             instance.launchpad.launchpad_treasury = Some(launchpad_treasury);
+            instance.launchpad.storage_version.set(&STORAGE_VERSION);
 
             instance
         }
 
         #[ink(message)]
-        #[modifiers(only_owner)]
+        #[modifiers(only_role(UPGRADER))]
         pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<(), PSP34Error> {
-            // TO DO: test set_code
-            ink::env::set_code_hash(&code_hash).unwrap_or_else(|err| {
-                panic!(
-                    "Failed to `set_code_hash` to {:?} due to {:?}",
-                    code_hash, err
-                )
-            });
+            self._check_timelock(TimelockOperation::SetCode(code_hash))?;
+            ink::env::set_code_hash(&code_hash).map_err(|_| {
+                PSP34Error::Custom(String::from(Shiden34Error::UpgradeFailed.as_str()))
+            })?;
             ink::env::debug_println!("Switched code hash to {:?}.", code_hash);
+            self.env().emit_event(Upgraded { code_hash });
             Ok(())
         }
+
+        /// Run the storage migrations the new code needs after an upgrade, going through at most
+        /// `limit` token IDs per call. Returns true once the storage is up to date, until then
+        /// tokens can't move and attributes can't change
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn migrate(&mut self, limit: u64) -> Result<bool, PSP34Error> {
+            let mut version = self.version();
+            if version >= STORAGE_VERSION {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::AlreadyMigrated.as_str(),
+                )));
+            }
+            if limit == 0 {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::InvalidInput.as_str(),
+                )));
+            }
+
+            let max_supply = self.launchpad.max_supply;
+            let mut remaining = limit;
+            while version < STORAGE_VERSION && remaining > 0 {
+                let cursor = self.launchpad.migration_cursor.get().unwrap_or_default();
+                let end = cursor.saturating_add(remaining).min(max_supply);
+                if version == 0 {
+                    self.migrate_v0_to_v1(cursor + 1, end);
+                } else if version == 1 {
+                    self.migrate_v1_to_v2(cursor + 1, end);
+                }
+                remaining -= end - cursor;
+
+                if end < max_supply {
+                    self.launchpad.migration_cursor.set(&end);
+                    break;
+                }
+                if version == 0 {
+                    self.finish_migrate_v0_to_v1();
+                }
+                self.launchpad.migration_cursor.set(&0);
+                self.launchpad.storage_version.set(&(version + 1));
+                self.env().emit_event(Migrated {
+                    from_version: version,
+                    to_version: version + 1,
+                });
+                version += 1;
+            }
+
+            Ok(version == STORAGE_VERSION)
+        }
+
+        #[ink(message)]
+        pub fn version(&self) -> u32 {
+            self.launchpad.storage_version.get().unwrap_or_default()
+        }
+
+        /// Index the registered attribute names and count the tokens from `start` to `end`
+        /// using each of them
+        fn migrate_v1_to_v2(&mut self, start: u64, end: u64) {
+            for index in 1..=self.launchpad.attribute_count {
+                if let Some(attribute_name) = self.launchpad.attribute_names.get(index) {
+                    let mut usage = match start {
+                        1 => 0,
+                        _ => self.launchpad.attribute_name_usage.get(index).unwrap_or(0),
+                    };
+                    for token_id in start..=end {
+                        if self
+                            .get_attribute(Id::U64(token_id), attribute_name.clone())
+                            .is_some()
+                        {
+                            usage += 1;
+                        }
+                    }
+                    self.launchpad
+                        .attribute_name_indexes
+                        .insert(&attribute_name, &index);
+                    self.launchpad.attribute_name_usage.insert(index, &usage);
+                }
+            }
+        }
+
+        /// Add the refundable sales of the live tokens from `start` to `end`, which unversioned
+        /// deployments lack
+        fn migrate_v0_to_v1(&mut self, start: u64, end: u64) {
+            let mut refundable_sales = self.launchpad.refundable_sales.get().unwrap_or_default();
+            for token_id in start..=end {
+                if let Some(minting_type) = self.launchpad.minting_type_for_token.get(token_id) {
+                    refundable_sales += launchpad::Internal::get_price_internal(self, minting_type);
+                }
+            }
+            self.launchpad.refundable_sales.set(&refundable_sales);
+        }
+
+        /// Seed the withdrawn ledger of both treasuries and the owner's roles
+        fn finish_migrate_v0_to_v1(&mut self) {
+            for (treasury, withdrawn) in [
+                (
+                    self.launchpad.project_treasury,
+                    self.launchpad.withdrawn_sales_project,
+                ),
+                (
+                    self.launchpad.launchpad_treasury,
+                    self.launchpad.withdrawn_sales_launchpad,
+                ),
+            ] {
+                if let Some(treasury) = treasury {
+                    let withdrawn_sales = self.launchpad.withdrawn_sales.get(treasury).unwrap_or(0);
+                    self.launchpad
+                        .withdrawn_sales
+                        .insert(treasury, &(withdrawn_sales + withdrawn));
+                }
+            }
+            self.launchpad.withdrawn_sales_project = 0;
+            self.launchpad.withdrawn_sales_launchpad = 0;
+
+            let owner = self.owner();
+            for role in [
+                DEFAULT_ADMIN_ROLE,
+                WHITELIST_MANAGER,
+                METADATA_ADMIN,
+                PHASE_ADMIN,
+                UPGRADER,
+            ] {
+                if !self.has_role(role, owner) {
+                    self._setup_role(role, owner);
+                }
+            }
+        }
     }
 
     // Override event emission methods
     impl psp34::Internal for ParasRefundableContract {
+        fn _before_token_transfer(
+            &mut self,
+            from: Option<&AccountId>,
+            to: Option<&AccountId>,
+            id: &Id,
+        ) -> Result<(), PSP34Error> {
+            // the running migration sums up the refundable sales of the tokens
+            if self.version() < STORAGE_VERSION {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::MigrationPending.as_str(),
+                )));
+            }
+            self._check_transfer_allowed(from, to, id)?;
+            self._record_transfer_price(from, to, id);
+            Ok(())
+        }
+
         fn _emit_transfer_event(&self, from: Option<AccountId>, to: Option<AccountId>, id: Id) {
             self.env().emit_event(Transfer { from, to, id });
         }
@@ -198,8 +482,92 @@ pub mod paras_refundable {
                 refunded,
             })
         }
+
+        fn _emit_milestone_approved_event(&self, index: u32, approver: AccountId) {
+            self.env().emit_event(MilestoneApproved { index, approver })
+        }
+
+        fn _emit_project_defaulted_event(&self, proposal_id: Option<u32>, refund_ratio: u128) {
+            self.env().emit_event(ProjectDefaulted {
+                proposal_id,
+                refund_ratio,
+            })
+        }
+
+        fn _emit_withdrawn_event(&self, payer: AccountId, destination: AccountId, amount: Balance) {
+            self.env().emit_event(Withdrawn {
+                payer,
+                destination,
+                amount,
+            })
+        }
+
+        fn _emit_address_change_proposed_event(
+            &self,
+            address_kind: u8,
+            proposer: AccountId,
+            new_address: AccountId,
+        ) {
+            self.env().emit_event(AddressChangeProposed {
+                address_kind,
+                proposer,
+                new_address,
+            })
+        }
+
+        fn _emit_address_changed_event(
+            &self,
+            address_kind: u8,
+            old_address: Option<AccountId>,
+            new_address: AccountId,
+        ) {
+            self.env().emit_event(AddressChanged {
+                address_kind,
+                old_address,
+                new_address,
+            })
+        }
+
+        fn _emit_refund_waived_event(&self, holder: AccountId, token_id: u64) {
+            self.env().emit_event(RefundWaived { holder, token_id })
+        }
+    }
+    impl Psp34Traits for ParasRefundableContract {
+        fn _emit_metadata_frozen_event(&self, token_id: Option<u64>) {
+            self.env().emit_event(MetadataFrozen { token_id })
+        }
+
+        fn _emit_attributes_batch_set_event(&self, updated: u32, failed: u32) {
+            self.env()
+                .emit_event(AttributesBatchSet { updated, failed })
+        }
+    }
+    impl Royalty for ParasRefundableContract {}
+
+    impl Timelock for ParasRefundableContract {
+        fn _emit_operation_scheduled_event(
+            &self,
+            operation_id: u32,
+            operation: TimelockOperation,
+            proposer: AccountId,
+            ready_at: BlockTimestamp,
+        ) {
+            self.env().emit_event(OperationScheduled {
+                operation_id,
+                operation,
+                proposer,
+                ready_at,
+            })
+        }
+
+        fn _emit_operation_executed_event(&self, operation_id: u32) {
+            self.env().emit_event(OperationExecuted { operation_id })
+        }
+
+        fn _emit_operation_cancelled_event(&self, operation_id: u32) {
+            self.env().emit_event(OperationCancelled { operation_id })
+        }
     }
-    impl Psp34Traits for ParasRefundableContract {}
 
     // ------------------- T E S T -----------------------------------------------------
     #[cfg(test)]
@@ -310,6 +678,40 @@ pub mod paras_refundable {
             assert!(sh34.set_minting_status(Some(1)).is_err()); // prepresale
         }
 
+        #[ink::test]
+        fn whitelist_manager_role_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.add_account_to_presale(accounts.eve, 1),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.grant_role(WHITELIST_MANAGER, accounts.bob).is_ok());
+
+            set_sender(accounts.bob);
+            assert!(sh34.add_account_to_presale(accounts.eve, 1).is_ok());
+            assert_eq!(sh34.get_account_presale_minting_amount(accounts.eve), 1);
+            assert_eq!(
+                sh34.set_minting_status(Some(2)),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+            assert_eq!(
+                sh34.set_code([0; 32]),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.revoke_role(WHITELIST_MANAGER, accounts.bob).is_ok());
+            assert!(!sh34.has_role(WHITELIST_MANAGER, accounts.bob));
+
+            set_sender(accounts.bob);
+            assert!(sh34.add_account_to_presale(accounts.eve, 2).is_err());
+        }
+
         #[ink::test]
         fn add_to_presale_and_prepresale_works() {
             let mut sh34 = init();
@@ -387,6 +789,95 @@ pub mod paras_refundable {
             assert_eq!(1, ink::env::test::recorded_events().count());
         }
 
+        #[ink::test]
+        fn transfer_lock_works() {
+            use crate::paras_refundable::Id::U64;
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_transfer_lock(true),
+                Err(PSP34Error::Custom(String::from("O::CallerIsNotOwner")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_transfer_lock(true).is_ok());
+            assert!(sh34.get_transfer_lock());
+            assert!(sh34.set_minting_status(Some(3)).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRICE * 3);
+            let mut token_ids = Vec::new();
+            for index in 0..3 {
+                assert!(pay_with_call!(sh34.mint_next(), PRICE).is_ok());
+                token_ids.push(
+                    match sh34
+                        .owners_token_by_index(accounts.bob, index)
+                        .ok()
+                        .unwrap()
+                    {
+                        U64(value) => value,
+                        _ => 0,
+                    },
+                );
+            }
+
+            // buyers minted under the lock
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_transfer_lock(false),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::TransferLockLocked.as_str()
+                ))
+            );
+            assert!(sh34.get_transfer_lock());
+            set_sender(accounts.bob);
+
+            assert!(!sh34.is_transferable(token_ids[0]));
+            assert_eq!(
+                sh34.transfer(accounts.django, U64(token_ids[0]), Vec::new()),
+                Err(PSP34Error::Custom(Shiden34Error::TransferLocked.as_str()))
+            );
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                sh34.waive_refund(token_ids[0]),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+
+            set_sender(accounts.bob);
+            assert!(sh34.waive_refund(token_ids[0]).is_ok());
+            assert!(sh34.is_transferable(token_ids[0]));
+            assert!(sh34
+                .transfer(accounts.django, U64(token_ids[0]), Vec::new())
+                .is_ok());
+
+            set_sender(accounts.django);
+            assert_eq!(
+                sh34.waive_refund(token_ids[0]),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::TokenNotRefundable.as_str()
+                ))
+            );
+
+            // refunds still move locked tokens to the refund address
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + 1);
+            set_sender(accounts.bob);
+            assert!(sh34.refund(token_ids[1]).is_ok());
+            assert_eq!(sh34.get_refund_liability(), (PRICE * 95) / 100);
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + 3 * ONE_MONTH_IN_MILLIS + 1,
+            );
+            assert!(sh34.is_transferable(token_ids[2]));
+            assert!(sh34
+                .transfer(accounts.django, U64(token_ids[2]), Vec::new())
+                .is_ok());
+        }
+
         #[ink::test]
         fn refund_presale_works() {
             use crate::paras_refundable::Id::U64;
@@ -430,40 +921,244 @@ pub mod paras_refundable {
         }
 
         #[ink::test]
-        fn withdraw_launchpad_works() {
+        fn refund_schedule_works() {
+            use crate::paras_refundable::Id::U64;
             let mut sh34 = init();
             let accounts = default_accounts();
-            assert_eq!(sh34.owner(), accounts.alice);
 
             set_sender(accounts.alice);
             assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
             assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
 
             set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            let bob_token_id: u64 = match sh34.owners_token_by_index(accounts.bob, 0).ok().unwrap()
+            {
+                U64(value) => value,
+                _ => 0,
+            };
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS + 1,
+            );
+            assert_eq!(
+                sh34.get_refund_schedule(bob_token_id),
+                [
+                    (
+                        PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 2,
+                        (PRESALE_PRICE * 85) / 100
+                    ),
+                    (
+                        PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 3,
+                        (PRESALE_PRICE * 70) / 100
+                    ),
+                ]
+                .to_vec()
+            );
+            assert_eq!(
+                sh34.get_refund_amount_at(bob_token_id, PUBLIC_SALE_END_AT + 1),
+                (PRESALE_PRICE * 95) / 100
+            );
+            assert_eq!(
+                sh34.get_refund_amount_at(
+                    bob_token_id,
+                    PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 3
+                ),
+                0
+            );
+            assert!(sh34.get_refund_schedule(MAX_SUPPLY + 1).is_empty());
+        }
+
+        #[ink::test]
+        fn refund_credited_when_transfer_fails() {
+            use crate::paras_refundable::Id::U64;
+            let mut sh34 = init();
+            let accounts = default_accounts();
 
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+
+            set_sender(accounts.bob);
             test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
             assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
 
+            let bob_token_id: u64 = match sh34.owners_token_by_index(accounts.bob, 0).ok().unwrap()
+            {
+                U64(value) => value,
+                _ => 0,
+            };
+
             set_sender(accounts.alice);
             assert!(sh34.set_minting_status(Some(4)).is_ok());
 
-            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.django, 0);
-
-            // first month withdraw
+            // the native transfer fails, the refund is kept as a pending credit
+            let contract_balance = sh34.env().balance();
+            set_balance(sh34.env().account_id(), 0);
             test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + 1);
-
-            set_sender(accounts.django);
+            set_sender(accounts.bob);
+            assert_eq!(sh34.refund(bob_token_id), Ok((PRESALE_PRICE * 95) / 100));
+            assert_eq!(sh34.balance_of(accounts.bob), 0);
             assert_eq!(
-                sh34.get_available_to_withdraw_launchpad(),
-                (PRESALE_PRICE * 5 * 10) / (100 * 100)
+                sh34.get_pending_credit(accounts.bob),
+                (PRESALE_PRICE * 95) / 100
             );
-            assert!(sh34.withdraw_launchpad().is_ok());
 
+            // once funds are available again the credit can be claimed to any account
+            set_balance(sh34.env().account_id(), contract_balance);
+            set_balance(accounts.eve, 0);
+            assert_eq!(sh34.claim(accounts.eve), Ok((PRESALE_PRICE * 95) / 100));
             assert_eq!(
-                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.django)
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.eve)
                     .ok()
                     .unwrap(),
-                (PRESALE_PRICE * 5 * 10) / (100 * 100)
+                (PRESALE_PRICE * 95) / 100
+            );
+            assert_eq!(sh34.get_pending_credit(accounts.bob), 0);
+            assert_eq!(
+                sh34.claim(accounts.eve),
+                Err(PSP34Error::Custom(Shiden34Error::NothingToClaim.as_str()))
+            );
+        }
+
+        #[ink::test]
+        fn refund_original_minter_only_rejects_secondary_holder() {
+            use crate::paras_refundable::Id::U64;
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+            assert!(sh34.set_refund_policy(1).is_ok()); // original minter only
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            let bob_token_id: u64 = match sh34.owners_token_by_index(accounts.bob, 0).ok().unwrap()
+            {
+                U64(value) => value,
+                _ => 0,
+            };
+            assert_eq!(sh34.get_original_minter(bob_token_id), Some(accounts.bob));
+            assert!(sh34
+                .transfer(accounts.eve, U64(bob_token_id), Vec::new())
+                .is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + 1);
+            set_sender(accounts.eve);
+            assert_eq!(
+                sh34.refund(bob_token_id),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::RefundNotEligible.as_str()
+                ))
+            );
+            assert_eq!(sh34.balance_of(accounts.eve), 1);
+        }
+
+        #[ink::test]
+        fn refund_capped_at_last_sale_price_works() {
+            use crate::paras_refundable::Id::U64;
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+            assert!(sh34.set_refund_policy(2).is_ok()); // capped at last sale price
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            let bob_token_id: u64 = match sh34.owners_token_by_index(accounts.bob, 0).ok().unwrap()
+            {
+                U64(value) => value,
+                _ => 0,
+            };
+            assert_eq!(sh34.get_last_sale_price(bob_token_id), PRESALE_PRICE);
+            assert!(sh34.approve(accounts.frank, None, true).is_ok());
+
+            // only marketplaces report resale prices
+            assert_eq!(
+                sh34.transfer_sold(accounts.eve, bob_token_id, PRESALE_PRICE / 2),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+            set_sender(accounts.alice);
+            assert!(sh34.grant_role(MARKETPLACE, accounts.frank).is_ok());
+            set_sender(accounts.frank);
+            assert!(sh34
+                .transfer_sold(accounts.eve, bob_token_id, PRESALE_PRICE / 2)
+                .is_ok());
+            assert_eq!(sh34.owner_of(U64(bob_token_id)), Some(accounts.eve));
+            assert_eq!(sh34.get_last_sale_price(bob_token_id), PRESALE_PRICE / 2);
+
+            // the policy holders bought under can't change anymore
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_refund_policy(0),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::RefundPolicyLocked.as_str()
+                ))
+            );
+            assert_eq!(sh34.get_refund_policy(), 2);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + 1);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.eve, 0);
+            set_sender(accounts.eve);
+            // refunded the resale price instead of 95% of the mint price
+            assert_eq!(sh34.refund(bob_token_id), Ok(PRESALE_PRICE / 2));
+            assert!(PRESALE_PRICE / 2 < PRESALE_PRICE * 95 / 100);
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.eve)
+                    .ok()
+                    .unwrap(),
+                PRESALE_PRICE / 2
+            );
+        }
+
+        #[ink::test]
+        fn withdraw_launchpad_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            assert_eq!(sh34.owner(), accounts.alice);
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+
+            set_sender(accounts.bob);
+
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.django, 0);
+
+            // first month withdraw
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + 1);
+
+            set_sender(accounts.django);
+            assert_eq!(
+                sh34.get_available_to_withdraw_launchpad(),
+                (PRESALE_PRICE * 5 * 10) / (100 * 100)
+            );
+            assert!(sh34.withdraw_launchpad().is_ok());
+
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.django)
+                    .ok()
+                    .unwrap(),
+                (PRESALE_PRICE * 5 * 10) / (100 * 100)
             );
 
             // second month withdraw
@@ -511,7 +1206,7 @@ pub mod paras_refundable {
                 (PRESALE_PRICE * 10) / 100
             );
 
-            assert_eq!(1, ink::env::test::recorded_events().count());
+            assert_eq!(5, ink::env::test::recorded_events().count());
         }
 
         #[ink::test]
@@ -593,7 +1288,7 @@ pub mod paras_refundable {
                 (PRESALE_PRICE * 90) / 100
             );
 
-            assert_eq!(1, ink::env::test::recorded_events().count());
+            assert_eq!(5, ink::env::test::recorded_events().count());
         }
 
         #[ink::test]
@@ -696,65 +1391,1586 @@ pub mod paras_refundable {
         }
 
         #[ink::test]
-        fn token_uri_works() {
+        fn default_vote_works() {
             use crate::paras_refundable::Id::U64;
-
             let mut sh34 = init();
             let accounts = default_accounts();
-            set_sender(accounts.alice);
 
+            set_sender(accounts.alice);
             assert!(sh34.set_minting_status(Some(3)).is_ok());
-            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
-            let mint_result = sh34.mint_next();
-            assert!(mint_result.is_ok());
-            // return error if request is for not yet minted token
+            assert_eq!(
+                sh34.set_default_vote_config(50, 60, 0),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidInput.as_str()))
+            );
+            assert_eq!(
+                sh34.set_default_vote_config(100, 60, ONE_MONTH_IN_MILLIS),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidInput.as_str()))
+            );
+            assert!(sh34
+                .set_default_vote_config(50, 60, ONE_MONTH_IN_MILLIS)
+                .is_ok());
 
-            let alice_token_id: u64 =
-                match sh34.owners_token_by_index(accounts.alice, 0).ok().unwrap() {
-                    U64(value) => value,
-                    _ => 0,
-                };
+            set_sender(accounts.bob);
+            set_balance(accounts.bob, PRICE * 2);
+            assert!(pay_with_call!(sh34.mint(accounts.bob, 2), PRICE * 2).is_ok());
+
+            // buyers minted under the announced vote rules
+            set_sender(accounts.alice);
             assert_eq!(
-                sh34.token_uri(alice_token_id),
-                PreludeString::from(
-                    BASE_URI.to_owned() + format!("{}.json", alice_token_id).as_str()
-                )
+                sh34.set_default_vote_config(10, 60, ONE_MONTH_IN_MILLIS),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::DefaultVoteConfigLocked.as_str()
+                ))
             );
+            assert_eq!(
+                sh34.get_default_vote_config(),
+                (50, 60, ONE_MONTH_IN_MILLIS)
+            );
+            set_sender(accounts.bob);
+            let token_ids: Vec<u64> = (0..2)
+                .map(|index| {
+                    match sh34
+                        .owners_token_by_index(accounts.bob, index)
+                        .ok()
+                        .unwrap()
+                    {
+                        U64(value) => value,
+                        _ => 0,
+                    }
+                })
+                .collect();
 
-            // verify token_uri when baseUri is empty
             set_sender(accounts.alice);
-            assert!(sh34.set_base_uri(PreludeString::from("")).is_ok());
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + 1);
+
+            // only token holders can vote, once per token
+            set_sender(accounts.bob);
+            assert_eq!(sh34.propose_default(), Ok(1));
+            set_sender(accounts.eve);
             assert_eq!(
-                sh34.token_uri(alice_token_id),
-                PreludeString::from("".to_owned() + format!("{}.json", alice_token_id).as_str())
+                sh34.vote_default(token_ids.clone(), true),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+            set_sender(accounts.bob);
+            assert!(sh34.vote_default(token_ids.clone(), true).is_ok());
+            assert_eq!(
+                sh34.vote_default(token_ids.clone(), true),
+                Err(PSP34Error::Custom(Shiden34Error::AlreadyVoted.as_str()))
+            );
+            assert_eq!(
+                sh34.finalize_default(),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::DefaultVotingNotEnded.as_str()
+                ))
+            );
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS + 1,
+            );
+            assert_eq!(sh34.finalize_default(), Ok(true));
+            assert!(sh34.is_project_defaulted());
+            assert_eq!(sh34.get_default_proposal(), None);
+
+            // project withdrawals stop and the escrow minus the launchpad fee is refunded pro-rata
+            assert_eq!(sh34.get_available_to_withdraw_project(), 0);
+            let launchpad_share = (PRICE * 2 * 15 * 10) / (100 * 100);
+            let refund_amount = (PRICE * 2 - launchpad_share) / 2;
+            assert_eq!(sh34.get_refund_amount(token_ids[0]), refund_amount);
+            assert_eq!(sh34.get_refund_liability(), refund_amount * 2);
+
+            set_balance(accounts.bob, 0);
+            assert_eq!(sh34.refund(token_ids[0]), Ok(refund_amount));
+            assert_eq!(sh34.refund(token_ids[1]), Ok(refund_amount));
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob)
+                    .ok()
+                    .unwrap(),
+                refund_amount * 2
             );
+
+            set_sender(accounts.django);
+            assert_eq!(sh34.get_available_to_withdraw_launchpad(), launchpad_share);
         }
 
         #[ink::test]
-        fn owner_is_set() {
+        fn withdraw_project_milestones_works() {
+            let mut sh34 = init();
             let accounts = default_accounts();
-            let sh34 = init();
-            assert_eq!(sh34.owner(), accounts.alice);
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+            assert!(sh34.set_milestones([40, 40].to_vec()).is_err());
+            assert!(sh34.set_milestones([40, 60].to_vec()).is_ok());
+            assert!(sh34.set_milestone_arbiter(Some(accounts.frank)).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            // nothing is released before the first milestone is approved
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 3 + 1,
+            );
+            assert_eq!(sh34.get_available_to_withdraw_project(), 0);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.approve_milestone(0),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+            set_sender(accounts.frank);
+            assert!(sh34.approve_milestone(0).is_ok());
+            assert_eq!(
+                sh34.approve_milestone(0),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::MilestoneAlreadyApproved.as_str()
+                ))
+            );
+            assert_eq!(sh34.get_milestones(), [(40, true), (60, false)].to_vec());
+            assert_eq!(
+                sh34.get_available_to_withdraw_project(),
+                (PRESALE_PRICE * 90 * 40) / (100 * 100)
+            );
+
+            set_sender(accounts.django);
+            assert!(sh34.approve_milestone(1).is_ok());
+            assert_eq!(
+                sh34.get_available_to_withdraw_project(),
+                (PRESALE_PRICE * 90) / 100
+            );
         }
 
         #[ink::test]
-        fn set_base_uri_works() {
-            let accounts = default_accounts();
-            const NEW_BASE_URI: &str = "new_uri/";
+        fn withdraw_project_payees_works() {
             let mut sh34 = init();
+            let accounts = default_accounts();
 
             set_sender(accounts.alice);
-            let collection_id = sh34.collection_id();
-            assert!(sh34.set_base_uri(NEW_BASE_URI.into()).is_ok());
             assert_eq!(
-                sh34.get_attribute(collection_id, String::from("baseUri")),
-                Some(String::from(NEW_BASE_URI))
+                sh34.get_project_payees(),
+                [(accounts.charlie, 10000)].to_vec()
+            );
+            assert_eq!(
+                sh34.set_project_payees(
+                    [(accounts.charlie, 7000), (accounts.frank, 2000)].to_vec()
+                ),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidInput.as_str()))
+            );
+            assert_eq!(
+                sh34.set_project_payees(
+                    [(accounts.charlie, 7000), (accounts.django, 3000)].to_vec()
+                ),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidInput.as_str()))
             );
+            assert!(sh34
+                .set_project_payees([(accounts.charlie, 7000), (accounts.frank, 3000)].to_vec())
+                .is_ok());
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+
             set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 3 + 1,
+            );
+            let project_share = (PRESALE_PRICE * 90) / 100;
+            assert_eq!(sh34.get_available_to_withdraw_project(), project_share);
             assert_eq!(
-                sh34.set_base_uri(NEW_BASE_URI.into()),
-                Err(PSP34Error::Custom(String::from("O::CallerIsNotOwner")))
+                sh34.get_available_to_withdraw_payee(accounts.frank),
+                (project_share * 3000) / 10000
+            );
+
+            set_sender(accounts.eve);
+            assert_eq!(
+                sh34.withdraw_project(),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.frank, 0);
+            set_sender(accounts.frank);
+            assert!(sh34.withdraw_project().is_ok());
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.frank)
+                    .ok()
+                    .unwrap(),
+                (project_share * 3000) / 10000
+            );
+            assert_eq!(
+                sh34.get_withdrawn_sales(accounts.frank),
+                (project_share * 3000) / 10000
+            );
+            assert_eq!(sh34.get_available_to_withdraw_payee(accounts.frank), 0);
+            assert_eq!(
+                sh34.get_available_to_withdraw_project(),
+                (project_share * 7000) / 10000
+            );
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_project_payees([(accounts.charlie, 10000)].to_vec()),
+                Err(PSP34Error::Custom(Shiden34Error::PayeesLocked.as_str()))
+            );
+        }
+
+        #[ink::test]
+        fn rotate_project_treasury_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            // first month withdraw by the old treasury
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + 1);
+            set_sender(accounts.charlie);
+            assert!(sh34.withdraw_project().is_ok());
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.propose_address_change(0, accounts.bob),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.propose_address_change(1, accounts.alice),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+            assert_eq!(
+                sh34.accept_address_change(0),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::NoPendingAddressChange.as_str()
+                ))
+            );
+
+            set_sender(accounts.charlie);
+            assert!(sh34.propose_address_change(0, accounts.frank).is_ok());
+            assert_eq!(sh34.get_pending_address_change(0), Some(accounts.frank));
+            assert_eq!(
+                sh34.accept_address_change(0),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+
+            set_sender(accounts.frank);
+            assert!(sh34.accept_address_change(0).is_ok());
+            assert_eq!(sh34.get_pending_address_change(0), None);
+            assert_eq!(sh34.get_project_treasury_address(), accounts.frank);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                sh34.withdraw_project(),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+
+            // after 3 months the new treasury only gets what the old one did not withdraw
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 3 + 1,
+            );
+            assert_eq!(
+                sh34.get_available_to_withdraw_project(),
+                (PRESALE_PRICE * 90) / 100
+                    - ((PRESALE_PRICE * 5) / 100 - (PRESALE_PRICE * 5 * 10) / (100 * 100))
+            );
+
+            // an address taken between the proposal and its acceptance is refused
+            set_sender(accounts.django);
+            assert!(sh34.propose_address_change(1, accounts.eve).is_ok());
+            set_sender(accounts.frank);
+            assert!(sh34.propose_address_change(0, accounts.eve).is_ok());
+            set_sender(accounts.eve);
+            assert!(sh34.accept_address_change(0).is_ok());
+            assert_eq!(
+                sh34.accept_address_change(1),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidInput.as_str()))
+            );
+            assert_eq!(sh34.get_launchpad_treasury_address(), accounts.django);
+            assert_eq!(7, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn withdraw_project_vesting_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice);
+            assert!(sh34
+                .set_vesting_schedule(ONE_MONTH_IN_MILLIS * 5, ONE_MONTH_IN_MILLIS * 4)
+                .is_err());
+            assert!(sh34
+                .set_vesting_schedule(ONE_MONTH_IN_MILLIS * 2, ONE_MONTH_IN_MILLIS * 4)
+                .is_ok());
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_vesting_schedule(0, 0),
+                Err(PSP34Error::Custom(Shiden34Error::VestingLocked.as_str()))
+            );
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            // before the cliff everything released is locked
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS + 1,
+            );
+            assert_eq!(sh34.get_vested_project_share(), 0);
+            assert_eq!(
+                sh34.get_locked_project_share(),
+                (PRESALE_PRICE * 15 * 90) / (100 * 100)
+            );
+            assert_eq!(sh34.get_available_to_withdraw_project(), 0);
+
+            // about three of four months vested
+            let elapsed = ONE_MONTH_IN_MILLIS * 3 + 1;
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + elapsed);
+            let project_share = (PRESALE_PRICE * 90) / 100;
+            let vested_share =
+                (project_share * elapsed as Balance) / (ONE_MONTH_IN_MILLIS * 4) as Balance;
+            assert_eq!(sh34.get_vested_project_share(), vested_share);
+            assert_eq!(
+                sh34.get_locked_project_share(),
+                project_share - vested_share
+            );
+
+            set_sender(accounts.charlie);
+            assert!(sh34.withdraw_project().is_ok());
+            assert_eq!(sh34.get_withdrawn_project_share(), vested_share);
+            assert_eq!(sh34.get_available_to_withdraw_project(), 0);
+
+            // fully vested
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 4,
+            );
+            assert_eq!(sh34.get_locked_project_share(), 0);
+            assert_eq!(
+                sh34.get_available_to_withdraw_project(),
+                project_share - vested_share
+            );
+        }
+
+        #[ink::test]
+        fn withdraw_project_to_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 3 + 1,
+            );
+            let project_share = (PRESALE_PRICE * 90) / 100;
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.frank, 0);
+
+            set_sender(accounts.django);
+            assert_eq!(
+                sh34.withdraw_project_to(accounts.frank, 1),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                sh34.withdraw_project_to(accounts.frank, project_share + 1),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::AmountExceedsAvailable.as_str()
+                ))
+            );
+            assert!(sh34
+                .withdraw_project_to(accounts.frank, project_share / 3)
+                .is_ok());
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.frank)
+                    .ok()
+                    .unwrap(),
+                project_share / 3
+            );
+            assert_eq!(
+                sh34.get_available_to_withdraw_project(),
+                project_share - project_share / 3
+            );
+
+            set_sender(accounts.django);
+            assert!(sh34
+                .withdraw_launchpad_to(accounts.frank, PRESALE_PRICE / 10)
+                .is_ok());
+            assert_eq!(sh34.get_available_to_withdraw_launchpad(), 0);
+            assert_eq!(
+                test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.frank)
+                    .ok()
+                    .unwrap(),
+                project_share / 3 + PRESALE_PRICE / 10
+            );
+            assert_eq!(3, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn financial_summary_works() {
+            use crate::paras_refundable::Id::U64;
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 2).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(
+                accounts.bob,
+                PRESALE_PRICE * 2,
+            );
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            // first month, refund one token and withdraw the project share
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + 1);
+            let bob_token_id: u64 = match sh34.owners_token_by_index(accounts.bob, 0).ok().unwrap()
+            {
+                U64(value) => value,
+                _ => 0,
+            };
+            set_sender(accounts.bob);
+            assert!(sh34.refund(bob_token_id).is_ok());
+            set_sender(accounts.charlie);
+            assert!(sh34.withdraw_project().is_ok());
+
+            let project_withdrawn =
+                (PRESALE_PRICE * 2 * 5) / 100 - (PRESALE_PRICE * 2 * 5 * 10) / (100 * 100);
+            let summary = sh34.get_financial_summary();
+            assert_eq!(summary.total_sales, PRESALE_PRICE * 2);
+            assert_eq!(summary.total_refund, (PRESALE_PRICE * 95) / 100);
+            assert_eq!(summary.withdrawn_launchpad, 0);
+            assert_eq!(summary.withdrawn_project, project_withdrawn);
+            assert_eq!(
+                summary.available_to_withdraw_launchpad,
+                sh34.get_available_to_withdraw_launchpad()
+            );
+            assert_eq!(summary.available_to_withdraw_project, 0);
+            assert_eq!(
+                summary.project_payees,
+                [(accounts.charlie, project_withdrawn, 0)].to_vec()
+            );
+            assert_eq!(summary.refund_liability, (PRESALE_PRICE * 95) / 100);
+            assert_eq!(summary.contract_balance, sh34.env().balance());
+        }
+
+        #[ink::test]
+        fn declare_project_failed_works() {
+            use crate::paras_refundable::Id::U64;
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+            assert!(sh34.set_milestones([50, 50].to_vec()).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+            let bob_token_id: u64 = match sh34.owners_token_by_index(accounts.bob, 0).ok().unwrap()
+            {
+                U64(value) => value,
+                _ => 0,
+            };
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 3 + 1,
+            );
+
+            // the first tranche is withdrawn, the second one goes back to the holder
+            set_sender(accounts.django);
+            assert!(sh34.approve_milestone(0).is_ok());
+            set_sender(accounts.charlie);
+            assert!(sh34.withdraw_project().is_ok());
+            set_sender(accounts.django);
+            assert!(sh34.withdraw_launchpad().is_ok());
+            assert!(sh34.declare_project_failed().is_ok());
+            assert!(sh34.is_project_defaulted());
+
+            assert_eq!(
+                sh34.get_refund_amount(bob_token_id),
+                (PRESALE_PRICE * 90 * 50) / (100 * 100)
+            );
+        }
+
+        #[ink::test]
+        fn declare_project_failed_keeps_approved_tranches() {
+            use crate::paras_refundable::Id::U64;
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+            assert!(sh34.set_milestones([50, 50].to_vec()).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+            let bob_token_id: u64 = match sh34.owners_token_by_index(accounts.bob, 0).ok().unwrap()
+            {
+                U64(value) => value,
+                _ => 0,
+            };
+
+            // the tranches buyers minted under can't be dropped anymore
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_milestones(Vec::new()),
+                Err(PSP34Error::Custom(Shiden34Error::MilestonesLocked.as_str()))
+            );
+            set_sender(accounts.django);
+            assert_eq!(
+                sh34.declare_project_failed(),
+                Err(PSP34Error::Custom(Shiden34Error::MintingNotEnded.as_str()))
+            );
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 3 + 1,
+            );
+
+            // the first tranche is approved but not withdrawn when the project fails
+            set_sender(accounts.django);
+            assert!(sh34.approve_milestone(0).is_ok());
+            assert!(sh34.withdraw_launchpad().is_ok());
+            assert!(sh34.declare_project_failed().is_ok());
+
+            let tranche = (PRESALE_PRICE * 90 * 50) / (100 * 100);
+            assert_eq!(
+                sh34.get_refund_amount(bob_token_id),
+                PRESALE_PRICE * 90 / 100 - tranche
+            );
+            assert_eq!(sh34.get_available_to_withdraw_project(), tranche);
+            set_sender(accounts.charlie);
+            assert!(sh34.withdraw_project().is_ok());
+            assert!(
+                sh34.env().balance() - sh34.env().minimum_balance() >= sh34.get_refund_liability()
+            );
+        }
+
+        #[ink::test]
+        fn refund_liability_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            // first month, 95% of the sale is still refundable
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + 1);
+            assert_eq!(sh34.get_refund_liability(), (PRESALE_PRICE * 95) / 100);
+            assert_eq!(sh34.get_excess_balance(), (PRESALE_PRICE * 5) / 100);
+
+            // withdrawals never touch the refundable escrow
+            set_sender(accounts.django);
+            assert!(sh34.withdraw_launchpad().is_ok());
+            set_sender(accounts.charlie);
+            assert!(sh34.withdraw_project().is_ok());
+            assert_eq!(sh34.get_excess_balance(), 0);
+            assert!(
+                sh34.env().balance() - sh34.env().minimum_balance() >= sh34.get_refund_liability()
+            );
+
+            // after the last refund period nothing is owed anymore
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 3 + 1,
+            );
+            assert_eq!(sh34.get_refund_liability(), 0);
+        }
+
+        #[ink::test]
+        fn token_uri_works() {
+            use crate::paras_refundable::Id::U64;
+
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+
+            assert!(sh34.set_minting_status(Some(3)).is_ok());
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            let mint_result = sh34.mint_next();
+            assert!(mint_result.is_ok());
+            // return error if request is for not yet minted token
+
+            let alice_token_id: u64 =
+                match sh34.owners_token_by_index(accounts.alice, 0).ok().unwrap() {
+                    U64(value) => value,
+                    _ => 0,
+                };
+            assert_eq!(
+                sh34.token_uri(alice_token_id),
+                PreludeString::from(
+                    BASE_URI.to_owned() + format!("{}.json", alice_token_id).as_str()
+                )
+            );
+
+            // verify token_uri when baseUri is empty
+            set_sender(accounts.alice);
+            assert!(sh34.set_base_uri(PreludeString::from("")).is_ok());
+            assert_eq!(
+                sh34.token_uri(alice_token_id),
+                PreludeString::from("".to_owned() + format!("{}.json", alice_token_id).as_str())
+            );
+        }
+
+        #[ink::test]
+        fn uri_format_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+
+            assert_eq!(sh34.get_token_uri(1), Err(PSP34Error::TokenNotExists));
+            assert!(sh34.set_minting_status(Some(3)).is_ok());
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert!(sh34.mint(accounts.alice, 1).is_ok());
+            let token_id: u64 = match sh34.owners_token_by_index(accounts.alice, 0).ok().unwrap() {
+                Id::U64(value) => value,
+                _ => 0,
+            };
+            assert_eq!(
+                sh34.get_token_uri(token_id),
+                Ok(BASE_URI.to_owned() + format!("{}.json", token_id).as_str())
+            );
+
+            assert!(sh34.set_uri_format("".into(), 4, 0).is_ok());
+            assert_eq!(sh34.token_uri(26), BASE_URI.to_owned() + "0026");
+            assert!(sh34.set_uri_format(".json".into(), 64, 1).is_ok());
+            assert_eq!(
+                sh34.token_uri(26),
+                BASE_URI.to_owned() + format!("{:064x}.json", 26).as_str()
+            );
+            assert_eq!(sh34.get_uri_format(), (PreludeString::from(".json"), 64, 1));
+            assert_eq!(
+                sh34.set_uri_format("".into(), 65, 0),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidInput.as_str()))
+            );
+            assert_eq!(
+                sh34.set_uri_format("".into(), 0, 2),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidInput.as_str()))
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_uri_format("".into(), 0, 0),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+        }
+
+        #[ink::test]
+        fn owner_is_set() {
+            let accounts = default_accounts();
+            let sh34 = init();
+            assert_eq!(sh34.owner(), accounts.alice);
+        }
+
+        #[ink::test]
+        fn set_base_uri_works() {
+            let accounts = default_accounts();
+            const NEW_BASE_URI: &str = "new_uri/";
+            let mut sh34 = init();
+
+            set_sender(accounts.alice);
+            let collection_id = sh34.collection_id();
+            assert!(sh34.set_base_uri(NEW_BASE_URI.into()).is_ok());
+            assert_eq!(
+                sh34.get_attribute(collection_id, String::from("baseUri")),
+                Some(String::from(NEW_BASE_URI))
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_base_uri(NEW_BASE_URI.into()),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+        }
+
+        #[ink::test]
+        fn set_token_uri_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_token_uri(1, "ipfs://oneOfOne.json".into()),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_token_uri(1, "ipfs://oneOfOne.json".into()).is_ok());
+            assert!(sh34
+                .set_token_uri_batch(
+                    [
+                        (2, PreludeString::from("ipfs://ticket/2")),
+                        (3, PreludeString::from("ipfs://ticket/3"))
+                    ]
+                    .to_vec()
+                )
+                .is_ok());
+            assert_eq!(sh34.token_uri(1), "ipfs://oneOfOne.json");
+            assert_eq!(sh34.token_uri(3), "ipfs://ticket/3");
+            assert_eq!(sh34.token_uri(4), BASE_URI.to_owned() + "4.json");
+
+            assert!(sh34.remove_token_uri(1).is_ok());
+            assert!(sh34.remove_token_uri_batch([2].to_vec()).is_ok());
+            assert_eq!(sh34.token_uri(1), BASE_URI.to_owned() + "1.json");
+            assert_eq!(sh34.token_uri(2), BASE_URI.to_owned() + "2.json");
+            assert_eq!(sh34.token_uri(3), "ipfs://ticket/3");
+
+            assert!(sh34.freeze_token_metadata(3).is_ok());
+            assert_eq!(
+                sh34.remove_token_uri(3),
+                Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+            );
+        }
+
+        #[ink::test]
+        fn get_all_attributes_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+
+            set_sender(accounts.alice);
+            assert!(sh34
+                .set_multiple_attributes(
+                    Id::U64(1),
+                    [
+                        (
+                            PreludeString::from("background"),
+                            PreludeString::from("blue")
+                        ),
+                        (PreludeString::from("eyes"), PreludeString::from("red")),
+                    ]
+                    .to_vec()
+                )
+                .is_ok());
+            assert!(sh34
+                .set_multiple_attributes(
+                    Id::U64(2),
+                    [
+                        (PreludeString::from("hat"), PreludeString::from("cap")),
+                        (
+                            PreludeString::from("background"),
+                            PreludeString::from("green")
+                        ),
+                    ]
+                    .to_vec()
+                )
+                .is_ok());
+            // ids the contract never mints are rejected
+            for token_id in [Id::U64(MAX_SUPPLY + 1), Id::U8(1)] {
+                assert_eq!(
+                    sh34.set_multiple_attributes(
+                        token_id,
+                        [(PreludeString::from("hat"), PreludeString::from("crown"))].to_vec()
+                    ),
+                    Err(PSP34Error::Custom(Shiden34Error::InvalidInput.as_str()))
+                );
+            }
+
+            assert_eq!(sh34.get_attribute_count(), 3);
+            assert_eq!(
+                sh34.get_all_attributes(Id::U64(1)),
+                [
+                    (
+                        PreludeString::from("background"),
+                        PreludeString::from("blue")
+                    ),
+                    (PreludeString::from("eyes"), PreludeString::from("red")),
+                ]
+                .to_vec()
+            );
+            assert_eq!(
+                sh34.get_all_attributes(Id::U64(2)),
+                [
+                    (
+                        PreludeString::from("background"),
+                        PreludeString::from("green")
+                    ),
+                    (PreludeString::from("hat"), PreludeString::from("cap")),
+                ]
+                .to_vec()
+            );
+            // the first page holds `limit` names
+            assert_eq!(sh34.get_attributes_page(Id::U64(1), 0, 2).len(), 2);
+            assert_eq!(
+                sh34.get_attributes_page(Id::U64(1), 0, 1),
+                [(
+                    PreludeString::from("background"),
+                    PreludeString::from("blue")
+                )]
+                .to_vec()
+            );
+            assert_eq!(
+                sh34.get_attributes_page(Id::U64(2), 1, 2),
+                [(PreludeString::from("hat"), PreludeString::from("cap"))].to_vec()
+            );
+            assert!(sh34.get_attributes_page(Id::U64(2), 3, 10).is_empty());
+            assert!(sh34.get_all_attributes(Id::U64(3)).is_empty());
+        }
+
+        #[ink::test]
+        fn set_attributes_batch_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+            let attributes = [(PreludeString::from("hat"), PreludeString::from("cap"))].to_vec();
+
+            set_sender(accounts.alice);
+            assert!(sh34.freeze_token_metadata(3).is_ok());
+            assert_eq!(
+                sh34.set_attributes_batch(
+                    [
+                        (Id::U64(1), attributes.clone()),
+                        (Id::U64(0), attributes.clone()),
+                        (Id::U64(MAX_SUPPLY + 1), attributes.clone()),
+                        (Id::U64(3), attributes.clone()),
+                        (Id::U64(2), attributes.clone()),
+                    ]
+                    .to_vec()
+                ),
+                Ok([
+                    (
+                        Id::U64(0),
+                        PSP34Error::Custom(Shiden34Error::InvalidInput.as_str())
+                    ),
+                    (
+                        Id::U64(MAX_SUPPLY + 1),
+                        PSP34Error::Custom(Shiden34Error::InvalidInput.as_str())
+                    ),
+                    (
+                        Id::U64(3),
+                        PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str())
+                    ),
+                ]
+                .to_vec())
+            );
+            assert_eq!(sh34.get_all_attributes(Id::U64(1)), attributes);
+            assert_eq!(sh34.get_all_attributes(Id::U64(2)), attributes);
+            assert!(sh34.get_all_attributes(Id::U64(3)).is_empty());
+            assert_eq!(sh34.get_attribute_count(), 1);
+            assert_eq!(2, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn remove_attributes_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+
+            set_sender(accounts.alice);
+            assert!(sh34
+                .set_multiple_attributes(
+                    Id::U64(1),
+                    [
+                        (
+                            PreludeString::from("background"),
+                            PreludeString::from("blue")
+                        ),
+                        (PreludeString::from("eyes"), PreludeString::from("red")),
+                        (PreludeString::from("hat"), PreludeString::from("cap")),
+                    ]
+                    .to_vec()
+                )
+                .is_ok());
+            assert!(sh34
+                .set_multiple_attributes(
+                    Id::U64(2),
+                    [(
+                        PreludeString::from("background"),
+                        PreludeString::from("green")
+                    )]
+                    .to_vec()
+                )
+                .is_ok());
+            // overwriting a value does not count the name twice
+            assert!(sh34
+                .set_multiple_attributes(
+                    Id::U64(1),
+                    [(
+                        PreludeString::from("background"),
+                        PreludeString::from("red")
+                    )]
+                    .to_vec()
+                )
+                .is_ok());
+            assert_eq!(sh34.get_attribute_count(), 3);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.remove_attributes(Id::U64(1), [PreludeString::from("eyes")].to_vec()),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            // "eyes" is unused once removed, "hat" takes its index
+            set_sender(accounts.alice);
+            assert!(sh34
+                .remove_attributes(
+                    Id::U64(1),
+                    [PreludeString::from("eyes"), PreludeString::from("unknown")].to_vec()
+                )
+                .is_ok());
+            assert_eq!(sh34.get_attribute_count(), 2);
+            assert_eq!(sh34.get_attribute_name(2), "hat");
+            assert_eq!(sh34.get_attribute_name(3), "");
+            assert_eq!(
+                sh34.get_attributes(Id::U64(1), [PreludeString::from("eyes")].to_vec()),
+                [PreludeString::from("")].to_vec()
+            );
+
+            // "background" is still used by token 2
+            assert!(sh34
+                .remove_attributes(Id::U64(1), [PreludeString::from("background")].to_vec())
+                .is_ok());
+            assert_eq!(sh34.get_attribute_count(), 2);
+            assert_eq!(sh34.get_attribute_name(1), "background");
+            assert_eq!(
+                sh34.get_all_attributes(Id::U64(1)),
+                [(PreludeString::from("hat"), PreludeString::from("cap"))].to_vec()
+            );
+
+            assert!(sh34
+                .remove_attributes(Id::U64(2), [PreludeString::from("background")].to_vec())
+                .is_ok());
+            assert_eq!(sh34.get_attribute_count(), 1);
+            assert_eq!(sh34.get_attribute_name(1), "hat");
+        }
+
+        #[ink::test]
+        fn on_chain_metadata_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+
+            set_sender(accounts.alice);
+            assert!(sh34
+                .set_multiple_attributes(
+                    Id::U64(1),
+                    [
+                        (
+                            PreludeString::from("name"),
+                            PreludeString::from("Hero \"One\"")
+                        ),
+                        (
+                            PreludeString::from("description"),
+                            PreludeString::from("A hero")
+                        ),
+                        (
+                            PreludeString::from("image"),
+                            PreludeString::from("ipfs://img/1.png")
+                        ),
+                        (PreludeString::from("level"), PreludeString::from("5")),
+                    ]
+                    .to_vec()
+                )
+                .is_ok());
+            assert!(sh34
+                .set_metadata_template("Hero #{id}".into(), "Hero {id} of the collection".into())
+                .is_ok());
+            assert_eq!(sh34.token_uri(1), BASE_URI.to_owned() + "1.json");
+
+            assert!(sh34.set_on_chain_metadata(true).is_ok());
+            assert!(sh34.is_on_chain_metadata());
+            // {"name":"Hero \"One\"","description":"A hero","image":"ipfs://img/1.png","attributes":[{"trait_type":"level","value":"5"}]}
+            assert_eq!(
+                sh34.token_uri(1),
+                "data:application/json;base64,eyJuYW1lIjoiSGVybyBcIk9uZVwiIiwiZGVzY3JpcHRpb24iOiJBIGhlcm8iLCJpbWFnZSI6ImlwZnM6Ly9pbWcvMS5wbmciLCJhdHRyaWJ1dGVzIjpbeyJ0cmFpdF90eXBlIjoibGV2ZWwiLCJ2YWx1ZSI6IjUifV19"
+            );
+            // {"name":"Hero #2","description":"Hero 2 of the collection","image":"","attributes":[]}
+            assert_eq!(
+                sh34.token_uri(2),
+                "data:application/json;base64,eyJuYW1lIjoiSGVybyAjMiIsImRlc2NyaXB0aW9uIjoiSGVybyAyIG9mIHRoZSBjb2xsZWN0aW9uIiwiaW1hZ2UiOiIiLCJhdHRyaWJ1dGVzIjpbXX0="
+            );
+        }
+
+        #[ink::test]
+        fn freeze_metadata_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+            let attributes = [(PreludeString::from("level"), PreludeString::from("1"))].to_vec();
+
+            set_sender(accounts.alice);
+            assert!(sh34.freeze_token_metadata(1).is_ok());
+            assert!(sh34.is_metadata_frozen(1));
+            assert!(!sh34.is_metadata_frozen(2));
+            assert_eq!(
+                sh34.set_multiple_attributes(Id::U64(1), attributes.clone()),
+                Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+            );
+            assert!(sh34
+                .set_multiple_attributes(Id::U64(2), attributes.clone())
+                .is_ok());
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.freeze_metadata(),
+                Err(PSP34Error::Custom(String::from("O::CallerIsNotOwner")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34.freeze_metadata().is_ok());
+            assert!(sh34.is_metadata_frozen(2));
+            assert_eq!(
+                sh34.set_base_uri("new_uri/".into()),
+                Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+            );
+            assert_eq!(
+                sh34.set_multiple_attributes(Id::U64(2), attributes),
+                Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+            );
+            assert_eq!(
+                sh34.freeze_metadata(),
+                Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+            );
+            assert_eq!(2, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn contract_uri_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+
+            assert_eq!(sh34.contract_uri(), None);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_contract_uri("ipfs://collection.json".into()),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+
+            set_sender(accounts.alice);
+            assert!(sh34
+                .set_contract_uri("ipfs://collection.json".into())
+                .is_ok());
+            assert_eq!(
+                sh34.contract_uri(),
+                Some(PreludeString::from("ipfs://collection.json"))
+            );
+            assert!(sh34
+                .set_collection_attribute(0, "Refundable art".into())
+                .is_ok());
+            assert!(sh34
+                .set_collection_attribute(3, "https://paras.id".into())
+                .is_ok());
+            assert_eq!(
+                sh34.set_collection_attribute(6, "unknown".into()),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidInput.as_str()))
+            );
+            assert_eq!(
+                sh34.get_collection_attribute(0),
+                Some(PreludeString::from("Refundable art"))
+            );
+            assert_eq!(
+                sh34.get_attribute(sh34.collection_id(), String::from("externalUrl")),
+                Some(String::from("https://paras.id"))
+            );
+            assert!(sh34.set_collection_attribute(0, "".into()).is_ok());
+            assert_eq!(sh34.get_collection_attribute(0), None);
+
+            assert!(sh34.freeze_metadata().is_ok());
+            assert_eq!(
+                sh34.set_contract_uri("ipfs://other.json".into()),
+                Err(PSP34Error::Custom(Shiden34Error::MetadataFrozen.as_str()))
+            );
+        }
+
+        #[ink::test]
+        fn royalty_info_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+
+            assert_eq!(sh34.royalty_info(1, PRICE), None);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_default_royalty(Some((accounts.bob, 500))),
+                Err(PSP34Error::Custom(String::from("O::CallerIsNotOwner")))
+            );
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_default_royalty(Some((accounts.charlie, 10001))),
+                Err(PSP34Error::Custom(Shiden34Error::InvalidInput.as_str()))
+            );
+            assert!(sh34
+                .set_default_royalty(Some((accounts.charlie, 500)))
+                .is_ok());
+            assert!(sh34
+                .set_token_royalty(2, Some((accounts.frank, 1000)))
+                .is_ok());
+
+            assert_eq!(
+                sh34.royalty_info(1, PRICE),
+                Some((accounts.charlie, PRICE / 20))
+            );
+            assert_eq!(
+                sh34.royalty_info(2, PRICE),
+                Some((accounts.frank, PRICE / 10))
+            );
+            assert_eq!(
+                sh34.royalty_info(2, Balance::MAX),
+                Some((accounts.frank, Balance::MAX / 10000 * 1000))
+            );
+
+            assert!(sh34.set_token_royalty(2, None).is_ok());
+            assert_eq!(sh34.get_token_royalty(2), None);
+            assert_eq!(
+                sh34.royalty_info(2, PRICE),
+                Some((accounts.charlie, PRICE / 20))
+            );
+        }
+
+        #[ink::test]
+        fn timelock_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+            const ONE_DAY_IN_MILLIS: u64 = 86400000;
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_timelock_delay(ONE_DAY_IN_MILLIS).is_ok());
+            assert_eq!(
+                sh34.set_minting_status(Some(3)),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::OperationNotScheduled.as_str()
+                ))
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.schedule_operation(TimelockOperation::SetMintingStatus(Some(3))),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.schedule_operation(TimelockOperation::SetMintingStatus(Some(3))),
+                Ok(0)
+            );
+            assert_eq!(
+                sh34.schedule_operation(TimelockOperation::SetMintingStatus(Some(3))),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::OperationAlreadyScheduled.as_str()
+                ))
+            );
+            assert_eq!(
+                sh34.schedule_operation(TimelockOperation::SetBaseUri("new_uri/".into())),
+                Ok(1)
+            );
+            assert_eq!(sh34.get_pending_operations().len(), 2);
+            assert_eq!(
+                sh34.set_minting_status(Some(3)),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::OperationNotReady.as_str()
+                ))
+            );
+
+            // a shorter delay has to wait for the current one
+            assert_eq!(
+                sh34.set_timelock_delay(0),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::OperationNotScheduled.as_str()
+                ))
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.cancel_operation(1),
+                Err(PSP34Error::Custom(Shiden34Error::Unauthorized.as_str()))
+            );
+            set_sender(accounts.alice);
+            assert!(sh34.cancel_operation(1).is_ok());
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(ONE_DAY_IN_MILLIS);
+            assert!(sh34.set_minting_status(Some(3)).is_ok());
+            assert_eq!(sh34.get_minting_status(), String::from("public"));
+            assert!(sh34.get_pending_operations().is_empty());
+            assert_eq!(
+                sh34.set_base_uri("new_uri/".into()),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::OperationNotScheduled.as_str()
+                ))
+            );
+
+            // a huge delay saturates instead of overflowing the ready time
+            assert!(sh34.set_timelock_delay(u64::MAX).is_ok());
+            assert_eq!(
+                sh34.schedule_operation(TimelockOperation::SetBaseUri("new_uri/".into())),
+                Ok(2)
+            );
+            assert_eq!(sh34.get_pending_operations()[0].ready_at, u64::MAX);
+
+            // the pending list is bounded
+            for delay in 1..MAX_PENDING_OPERATIONS as u64 {
+                assert!(sh34
+                    .schedule_operation(TimelockOperation::SetTimelockDelay(delay))
+                    .is_ok());
+            }
+            assert_eq!(sh34.get_pending_operations().len(), MAX_PENDING_OPERATIONS);
+            assert_eq!(
+                sh34.schedule_operation(TimelockOperation::SetTimelockDelay(0)),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::TooManyPendingOperations.as_str()
+                ))
+            );
+            assert!(sh34.cancel_operation(2).is_ok());
+            assert_eq!(
+                sh34.get_pending_operations().len(),
+                MAX_PENDING_OPERATIONS - 1
+            );
+            assert_eq!(
+                5 + MAX_PENDING_OPERATIONS,
+                ink::env::test::recorded_events().count()
+            );
+        }
+
+        #[ink::test]
+        fn refund_parameters_only_protect_buyers_more_after_sale() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+
+            // anything goes before the first sale
+            set_sender(accounts.alice);
+            assert!(sh34.set_refund_periods([1, 2, 3].to_vec()).is_ok());
+            assert!(sh34.set_refund_shares([50, 40, 30].to_vec()).is_ok());
+            assert!(sh34.set_refund_shares([100, 40, 30].to_vec()).is_err());
+            assert!(sh34
+                .set_refund_periods(
+                    [
+                        ONE_MONTH_IN_MILLIS,
+                        ONE_MONTH_IN_MILLIS * 2,
+                        ONE_MONTH_IN_MILLIS * 3,
+                    ]
+                    .to_vec()
+                )
+                .is_ok());
+            assert!(sh34.set_minting_status(Some(3)).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_refund_periods([0, 0, 0].to_vec()),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::RefundPeriodShortened.as_str()
+                ))
+            );
+            assert_eq!(
+                sh34.set_refund_shares([50, 40, 20].to_vec()),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::RefundShareDecreased.as_str()
+                ))
+            );
+            assert!(sh34
+                .set_refund_periods(
+                    [
+                        ONE_MONTH_IN_MILLIS,
+                        ONE_MONTH_IN_MILLIS * 3,
+                        ONE_MONTH_IN_MILLIS * 4,
+                    ]
+                    .to_vec()
+                )
+                .is_ok());
+            assert!(sh34.set_refund_shares([60, 40, 30].to_vec()).is_ok());
+            assert_eq!(sh34.get_refund_shares(), [60, 40, 30].to_vec());
+        }
+
+        #[ink::test]
+        fn refund_after_extending_period_to_max_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(3)).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_refund_periods(
+                    [ONE_MONTH_IN_MILLIS, ONE_MONTH_IN_MILLIS * 2, u64::MAX].to_vec()
+                ),
+                Err(PSP34Error::Custom(String::from("InvalidInput")))
+            );
+            assert!(sh34
+                .set_refund_periods(
+                    [
+                        ONE_MONTH_IN_MILLIS,
+                        ONE_MONTH_IN_MILLIS * 2,
+                        MAX_REFUND_PERIOD,
+                    ]
+                    .to_vec()
+                )
+                .is_ok());
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + MAX_REFUND_PERIOD - 1,
+            );
+            set_sender(accounts.bob);
+            assert_eq!(sh34.refund(1), Ok(PRICE * 70 / 100));
+            assert_eq!(sh34.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn refund_parameters_cannot_reopen_paid_out_refunds() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(2)).is_ok()); // presale
+            assert!(sh34.add_account_to_presale(accounts.bob, 1).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRESALE_PRICE);
+            assert!(pay_with_call!(sh34.mint_next(), PRESALE_PRICE).is_ok());
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(4)).is_ok());
+
+            // first month, everything above the 95% escrow is paid out
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(PUBLIC_SALE_END_AT + 1);
+            set_sender(accounts.django);
+            assert!(sh34.withdraw_launchpad().is_ok());
+            set_sender(accounts.charlie);
+            assert!(sh34.withdraw_project().is_ok());
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_refund_shares([99, 85, 70].to_vec()),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::RefundLiabilityUncovered.as_str()
+                ))
+            );
+            assert!(sh34.set_refund_shares([95, 90, 70].to_vec()).is_ok());
+
+            // once the refunds ended and were paid out, the periods can't bring them back
+            test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PUBLIC_SALE_END_AT + ONE_MONTH_IN_MILLIS * 3 + 1,
+            );
+            set_sender(accounts.django);
+            assert!(sh34.withdraw_launchpad().is_ok());
+            set_sender(accounts.charlie);
+            assert!(sh34.withdraw_project().is_ok());
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                sh34.set_refund_periods(
+                    [
+                        ONE_MONTH_IN_MILLIS,
+                        ONE_MONTH_IN_MILLIS * 2,
+                        ONE_MONTH_IN_MILLIS * 4,
+                    ]
+                    .to_vec()
+                ),
+                Err(PSP34Error::Custom(
+                    Shiden34Error::RefundLiabilityUncovered.as_str()
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn migrate_populated_contract_works() {
+            let accounts = default_accounts();
+            let mut sh34 = init();
+            assert_eq!(sh34.version(), STORAGE_VERSION);
+
+            set_sender(accounts.alice);
+            assert!(sh34.set_minting_status(Some(3)).is_ok());
+            assert!(sh34.set_max_mint_amount(3).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.bob, PRICE * 3);
+            assert!(pay_with_call!(sh34.mint(accounts.bob, 3), PRICE * 3).is_ok());
+            let refundable_sales = sh34.launchpad.refundable_sales.get().unwrap_or_default();
+            assert_eq!(refundable_sales, PRICE * 3);
+
+            // storage as left by the code before versioning
+            sh34.launchpad.storage_version.set(&0);
+            sh34.launchpad.refundable_sales.set(&0);
+            sh34.launchpad.withdrawn_sales_project = PRICE / 10;
+            sh34.launchpad.withdrawn_sales_launchpad = PRICE / 100;
+            sh34.access = Default::default();
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.migrate(MAX_SUPPLY),
+                Err(PSP34Error::Custom(String::from("O::CallerIsNotOwner")))
+            );
+
+            // tokens stay put until every batch ran
+            set_sender(accounts.alice);
+            assert_eq!(sh34.migrate(4), Ok(false));
+            assert_eq!(sh34.version(), 0);
+            let token_id = sh34.owners_token_by_index(accounts.bob, 0).unwrap();
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.transfer(accounts.django, token_id, Vec::new()),
+                Err(PSP34Error::Custom(Shiden34Error::MigrationPending.as_str()))
+            );
+            assert_eq!(
+                sh34.mint_next(),
+                Err(PSP34Error::Custom(Shiden34Error::MigrationPending.as_str()))
+            );
+
+            set_sender(accounts.alice);
+            assert_eq!(sh34.migrate(MAX_SUPPLY), Ok(false));
+            assert_eq!(sh34.version(), 1);
+            assert_eq!(sh34.migrate(MAX_SUPPLY), Ok(true));
+            assert_eq!(sh34.version(), STORAGE_VERSION);
+            assert_eq!(
+                sh34.launchpad.refundable_sales.get(),
+                Some(refundable_sales)
+            );
+            assert_eq!(sh34.get_withdrawn_sales(accounts.charlie), PRICE / 10);
+            assert_eq!(sh34.get_withdrawn_sales(accounts.django), PRICE / 100);
+            assert_eq!(sh34.launchpad.withdrawn_sales_project, 0);
+            assert!(sh34.has_role(UPGRADER, accounts.alice));
+            assert_eq!(sh34.total_supply(), 3);
+            assert_eq!(
+                sh34.migrate(MAX_SUPPLY),
+                Err(PSP34Error::Custom(Shiden34Error::AlreadyMigrated.as_str()))
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_code([1; 32]),
+                Err(PSP34Error::Custom(String::from("AC::MissingRole")))
+            );
+            assert_eq!(5, ink::env::test::recorded_events().count());
+        }
+
+        /// Packed fields of `types::Data` in the order the first deployments stored them
+        #[derive(scale::Encode)]
+        struct DataV0 {
+            collection_id: u32,
+            max_supply: u64,
+            price_per_mint: Balance,
+            max_amount: u64,
+            token_set: Vec<u64>,
+            pseudo_random_salt: u64,
+            forced_minting_status: Option<u8>,
+            public_sale_start_at: u64,
+            public_sale_end_at: u64,
+            prepresale_start_at: u64,
+            prepresale_price_per_mint: Balance,
+            presale_start_at: u64,
+            presale_price_per_mint: Balance,
+            refund_periods: Vec<MilliSeconds>,
+            refund_shares: Vec<Percentage>,
+            refund_address: Option<AccountId>,
+            total_sales: Balance,
+            total_refund: Balance,
+            withdrawn_sales_project: Balance,
+            withdrawn_sales_launchpad: Balance,
+            launchpad_fee: Percentage,
+            project_treasury: Option<AccountId>,
+            launchpad_treasury: Option<AccountId>,
+            attribute_count: u32,
+        }
+
+        #[ink::test]
+        fn migrate_v0_encoded_storage_works() {
+            use ink::storage::traits::Storable;
+
+            let accounts = default_accounts();
+            let mut ownable = ownable::Data::default();
+            ownable.owner = accounts.alice;
+
+            // root cell of a contract deployed before versioning
+            let mut root = Vec::new();
+            Storable::encode(&psp34::Data::<enumerable::Balances>::default(), &mut root);
+            Storable::encode(&ownable, &mut root);
+            Storable::encode(&metadata::Data::default(), &mut root);
+            scale::Encode::encode_to(
+                &DataV0 {
+                    collection_id: 0,
+                    max_supply: MAX_SUPPLY,
+                    price_per_mint: PRICE,
+                    max_amount: 1,
+                    token_set: (3..=MAX_SUPPLY).collect(),
+                    pseudo_random_salt: 0,
+                    forced_minting_status: Some(4),
+                    public_sale_start_at: 0,
+                    public_sale_end_at: PUBLIC_SALE_END_AT,
+                    prepresale_start_at: 0,
+                    prepresale_price_per_mint: PREPRESALE_PRICE,
+                    presale_start_at: 0,
+                    presale_price_per_mint: PRESALE_PRICE,
+                    refund_periods: [ONE_MONTH_IN_MILLIS].to_vec(),
+                    refund_shares: [95].to_vec(),
+                    refund_address: Some(accounts.charlie),
+                    total_sales: PRICE + PRESALE_PRICE,
+                    total_refund: 0,
+                    withdrawn_sales_project: PRICE / 10,
+                    withdrawn_sales_launchpad: PRICE / 100,
+                    launchpad_fee: 10,
+                    project_treasury: Some(accounts.charlie),
+                    launchpad_treasury: Some(accounts.django),
+                    attribute_count: 0,
+                },
+                &mut root,
+            );
+
+            let mut input = &root[..];
+            let mut sh34 = <ParasRefundableContract as Storable>::decode(&mut input).unwrap();
+            assert!(input.is_empty());
+            assert_eq!(sh34.version(), 0);
+            assert_eq!(sh34.launchpad.refund_shares, [95].to_vec());
+            assert_eq!(sh34.launchpad.launchpad_treasury, Some(accounts.django));
+            sh34.launchpad.minting_type_for_token.insert(1, &3);
+            sh34.launchpad.minting_type_for_token.insert(2, &2);
+
+            set_sender(accounts.alice);
+            assert_eq!(sh34.migrate(MAX_SUPPLY * 2), Ok(true));
+            assert_eq!(sh34.version(), STORAGE_VERSION);
+            assert_eq!(
+                sh34.launchpad.refundable_sales.get(),
+                Some(PRICE + PRESALE_PRICE)
             );
+            assert_eq!(sh34.get_withdrawn_sales(accounts.charlie), PRICE / 10);
+            assert_eq!(sh34.get_withdrawn_sales(accounts.django), PRICE / 100);
+            assert!(sh34.has_role(UPGRADER, accounts.alice));
         }
 
         #[ink::test]
@@ -20,13 +20,20 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 use crate::impls::launchpad::types::{
-    Data, MilliSeconds, MintingStatus, Percentage, Shiden34Error,
+    AddressKind, AddressKindIndex, BasisPoints, CollectionAttribute, CollectionAttributeIndex,
+    Data, DefaultProposal, FinancialSummary, MilliSeconds, MintingStatus, MintingStatusIndex,
+    Percentage, RefundPolicy, Shiden34Error, TimelockOperation, CONTRACT_URI_ATTRIBUTE,
+    DEFAULT_REFUND_RATIO_PRECISION, MARKETPLACE, MAX_BASIS_POINTS, MAX_REFUND_PERIOD,
+    METADATA_ADMIN, PHASE_ADMIN, WHITELIST_MANAGER,
 };
+use crate::impls::psp34_traits::psp34_traits::Internal as _;
 pub use crate::traits::launchpad::Launchpad;
+use crate::traits::timelock::Timelock;
 
-use ink::prelude::vec::Vec;
+use ink::prelude::{string::String as PreludeString, vec::Vec};
 use openbrush::{
     contracts::{
+        access_control::{self, only_role},
         ownable::*,
         psp34::extensions::{enumerable::*, metadata::*},
     },
@@ -52,14 +59,108 @@ pub trait Internal {
 
     fn get_mint_id(&mut self) -> u64;
 
+    fn get_price_internal(&self, minting_type_index: MintingStatusIndex) -> Balance;
+
     fn get_refund_amount_and_price_internal(&self, token_id: u64) -> (Balance, Balance);
 
+    /// Get refund amount and price of a token at the given timestamp, regardless of minting status
+    fn get_refund_amount_and_price_at_internal(
+        &self,
+        token_id: u64,
+        timestamp: u64,
+    ) -> (Balance, Balance);
+
+    /// Check the refund policy for the given holder and return the refund amount they are entitled to
+    fn check_refund_eligibility(
+        &self,
+        token_id: u64,
+        account_id: AccountId,
+        refund_amount: Balance,
+    ) -> Result<Balance, PSP34Error>;
+
+    /// Get the refund share of the period the given timestamp falls in
+    fn get_refund_share_at_internal(&self, timestamp: u64) -> Option<Percentage>;
+
+    fn get_refund_liability_internal(&self) -> Balance;
+
+    /// Check the balance covers the refunds the given refund parameters lead to, now and in later periods
+    fn check_refund_liability_covered(
+        &self,
+        refund_periods: &[MilliSeconds],
+        refund_shares: &[Percentage],
+    ) -> Result<(), PSP34Error>;
+
+    fn get_excess_balance_internal(&self) -> Balance;
+
+    /// Check the account is the launchpad treasury or the milestone arbiter
+    fn check_milestone_approver(&self, account_id: AccountId) -> Result<(), PSP34Error>;
+
+    /// Stop project withdrawals and make the remaining escrow refundable pro-rata, returns the refund ratio
+    fn declare_default_internal(&mut self) -> u128;
+
+    /// Transfer the amount to the account, or credit it to the pending ledger if the transfer fails
+    fn pay_or_credit(&mut self, account_id: AccountId, amount: Balance);
+
+    /// Transfer the amount to the destination, or credit it to the account's pending ledger if the transfer fails
+    fn pay_to_or_credit(&mut self, destination: AccountId, account_id: AccountId, amount: Balance);
+
     fn get_available_to_withdraw_launchpad_internal(&self) -> Balance;
 
     fn get_available_to_withdraw_project_internal(&self) -> Balance;
 
+    /// Get the project payees, falling back to the project treasury when none were set
+    fn get_project_payees_internal(&self) -> Vec<(AccountId, BasisPoints)>;
+
+    /// Get the whole project share released so far, before it is split between payees
+    fn get_project_share_internal(&self) -> Balance;
+
+    /// Get the project share released so far, before the vesting schedule is applied
+    fn get_earned_project_share_internal(&self) -> Balance;
+
+    /// Get the part of the amount vested at the current timestamp
+    fn get_vested_amount_internal(&self, amount: Balance) -> Balance;
+
+    /// Get the total amount the account earned so far as launchpad treasury and project payee
+    fn get_accrued_sales_internal(&self, account_id: AccountId) -> Balance;
+
+    fn get_available_to_withdraw_payee_internal(&self, account_id: AccountId) -> Balance;
+
+    fn get_withdrawn_sales_project_internal(&self) -> Balance;
+
+    /// Record the amount as withdrawn by the payee and send it to the destination
+    fn withdraw_payee_internal(
+        &mut self,
+        account_id: AccountId,
+        destination: AccountId,
+        amount: Balance,
+    ) -> Result<(), PSP34Error>;
+
+    fn get_address_internal(&self, address_kind: &AddressKind) -> Option<AccountId>;
+
+    /// Check the address is not already used by the other treasury or a project payee
+    fn check_address_available(
+        &self,
+        address_kind: &AddressKind,
+        new_address: AccountId,
+    ) -> Result<(), PSP34Error>;
+
+    /// Replace the address, carrying the treasury withdrawal ledger and payee share over to the new one
+    fn set_address_internal(&mut self, address_kind: &AddressKind, new_address: AccountId);
+
     fn get_total_withdraw_share_internal(&self) -> u128;
 
+    /// Check the token could still be refunded, now or once the public sale ended
+    fn is_refundable_internal(&self, token_id: u64) -> bool;
+
+    /// Set or, when the value is empty, remove an attribute of the collection ID
+    fn set_collection_attribute_internal(
+        &mut self,
+        key: &str,
+        value: PreludeString,
+    ) -> Result<(), PSP34Error>;
+
+    fn get_collection_attribute_internal(&self, key: &str) -> Option<PreludeString>;
+
     fn check_and_update_allowed_to_mint(
         &mut self,
         account_id: AccountId,
@@ -75,9 +176,11 @@ where
     T: Storage<Data>
         + Storage<psp34::Data<enumerable::Balances>>
         + Storage<ownable::Data>
+        + Storage<access_control::Data>
         + Storage<metadata::Data>
         + psp34::extensions::metadata::PSP34Metadata
-        + psp34::Internal,
+        + psp34::Internal
+        + Timelock,
 {
     /// Mint one or more tokens
     default fn mint(&mut self, to: AccountId, mint_amount: u64) -> Result<(), PSP34Error> {
@@ -85,10 +188,12 @@ where
         let minting_status = self.get_current_minting_status();
         let transferred_value = Self::env().transferred_value();
 
+        self.check_migrated()?;
         self.check_amount(mint_amount)?;
         self.check_value(transferred_value, mint_amount, &minting_status)?;
         self.check_and_update_allowed_to_mint(caller_id, mint_amount, &minting_status)?;
 
+        let price = self.get_price_internal(minting_status.to_index());
         for _ in 0..mint_amount {
             let mint_id = self.get_mint_id();
             self.data::<psp34::Data<enumerable::Balances>>()
@@ -97,9 +202,20 @@ where
             self.data::<Data>()
                 .minting_type_for_token
                 .insert(mint_id, &minting_status.to_index());
+            self.data::<Data>().original_minter.insert(mint_id, &to);
+            self.data::<Data>().last_sale_price.insert(mint_id, &price);
         }
 
         self.data::<Data>().total_sales += transferred_value;
+        // same per-token price mint_next adds and refund takes back
+        let refundable_sales = self
+            .data::<Data>()
+            .refundable_sales
+            .get()
+            .unwrap_or_default();
+        self.data::<Data>()
+            .refundable_sales
+            .set(&(refundable_sales + price * mint_amount as Balance));
         Ok(())
     }
 
@@ -109,6 +225,7 @@ where
         let minting_status = self.get_current_minting_status();
         let transferred_value = Self::env().transferred_value();
 
+        self.check_migrated()?;
         self.check_amount(1)?;
         self.check_value(transferred_value, 1, &minting_status)?;
         self.check_and_update_allowed_to_mint(caller_id, 1, &minting_status)?;
@@ -122,52 +239,126 @@ where
         self.data::<Data>()
             .minting_type_for_token
             .insert(mint_id, &minting_status.to_index());
+        self.data::<Data>()
+            .original_minter
+            .insert(mint_id, &caller_id);
+        let price = self.get_price_internal(minting_status.to_index());
+        self.data::<Data>().last_sale_price.insert(mint_id, &price);
 
         self.data::<Data>().total_sales += transferred_value;
+        let refundable_sales = self
+            .data::<Data>()
+            .refundable_sales
+            .get()
+            .unwrap_or_default();
+        self.data::<Data>()
+            .refundable_sales
+            .set(&(refundable_sales + price));
         return Ok(());
     }
 
     /// Withdraws funds to contract owner
     default fn withdraw_launchpad(&mut self) -> Result<(), PSP34Error> {
         let caller_id = Self::env().caller();
+        let available_to_withdraw = self.get_available_to_withdraw_payee_internal(caller_id);
+        self.withdraw_launchpad_to(caller_id, available_to_withdraw)
+    }
 
-        if caller_id != self.data::<Data>().launchpad_treasury.unwrap() {
+    /// Withdraws the caller's share of the project funds
+    default fn withdraw_project(&mut self) -> Result<(), PSP34Error> {
+        let caller_id = Self::env().caller();
+        let available_to_withdraw = self.get_available_to_withdraw_payee_internal(caller_id);
+        self.withdraw_project_to(caller_id, available_to_withdraw)
+    }
+
+    default fn withdraw_launchpad_to(
+        &mut self,
+        destination: AccountId,
+        amount: Balance,
+    ) -> Result<(), PSP34Error> {
+        let caller_id = Self::env().caller();
+
+        if Some(caller_id) != self.data::<Data>().launchpad_treasury {
             return Err(PSP34Error::Custom(String::from(
                 Shiden34Error::Unauthorized.as_str(),
             )));
         }
 
-        let available_to_withdraw = self.get_available_to_withdraw_launchpad_internal();
+        self.withdraw_payee_internal(caller_id, destination, amount)?;
+        if amount > 0 {
+            self._emit_withdrawn_event(caller_id, destination, amount);
+        }
+        return Ok(());
+    }
 
-        self.data::<Data>().withdrawn_sales_launchpad += available_to_withdraw;
+    default fn withdraw_project_to(
+        &mut self,
+        destination: AccountId,
+        amount: Balance,
+    ) -> Result<(), PSP34Error> {
+        let caller_id = Self::env().caller();
 
-        Self::env()
-            .transfer(caller_id, available_to_withdraw)
-            .map_err(|_| {
-                PSP34Error::Custom(String::from(Shiden34Error::WithdrawalFailed.as_str()))
-            })?;
+        if !self
+            .get_project_payees_internal()
+            .iter()
+            .any(|(payee, _)| *payee == caller_id)
+        {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::Unauthorized.as_str(),
+            )));
+        }
+
+        self.withdraw_payee_internal(caller_id, destination, amount)?;
+        if amount > 0 {
+            self._emit_withdrawn_event(caller_id, destination, amount);
+        }
         return Ok(());
     }
 
-    default fn withdraw_project(&mut self) -> Result<(), PSP34Error> {
+    /// Claim payouts that could not be transferred when they were due
+    default fn claim(&mut self, to: AccountId) -> Result<Balance, PSP34Error> {
         let caller_id = Self::env().caller();
+        let pending_credit = self
+            .data::<Data>()
+            .pending_credits
+            .get(caller_id)
+            .unwrap_or(0);
 
-        if caller_id != self.data::<Data>().project_treasury.unwrap() {
+        if pending_credit == 0 {
             return Err(PSP34Error::Custom(String::from(
-                Shiden34Error::Unauthorized.as_str(),
+                Shiden34Error::NothingToClaim.as_str(),
             )));
         }
 
-        let available_to_withdraw = self.get_available_to_withdraw_project_internal();
+        self.data::<Data>().pending_credits.remove(caller_id);
+        let total_pending_credits = self
+            .data::<Data>()
+            .total_pending_credits
+            .get()
+            .unwrap_or_default();
+        self.data::<Data>()
+            .total_pending_credits
+            .set(&(total_pending_credits - pending_credit));
 
-        self.data::<Data>().withdrawn_sales_project += available_to_withdraw;
+        Self::env().transfer(to, pending_credit).map_err(|_| {
+            PSP34Error::Custom(String::from(Shiden34Error::WithdrawalFailed.as_str()))
+        })?;
+        Ok(pending_credit)
+    }
 
-        Self::env()
-            .transfer(caller_id, available_to_withdraw)
-            .map_err(|_| {
-                PSP34Error::Custom(String::from(Shiden34Error::WithdrawalFailed.as_str()))
-            })?;
-        return Ok(());
+    default fn get_pending_credit(&self, account_id: AccountId) -> Balance {
+        self.data::<Data>()
+            .pending_credits
+            .get(account_id)
+            .unwrap_or(0)
+    }
+
+    default fn get_refund_liability(&self) -> Balance {
+        self.get_refund_liability_internal()
+    }
+
+    default fn get_excess_balance(&self) -> Balance {
+        self.get_excess_balance_internal()
     }
 
     default fn get_available_to_withdraw_launchpad(&self) -> Balance {
@@ -178,6 +369,97 @@ where
         self.get_available_to_withdraw_project_internal()
     }
 
+    default fn get_financial_summary(&self) -> FinancialSummary {
+        let withdrawn_launchpad = match self.data::<Data>().launchpad_treasury {
+            Some(launchpad_treasury) => self
+                .data::<Data>()
+                .withdrawn_sales
+                .get(launchpad_treasury)
+                .unwrap_or_default(),
+            None => 0,
+        };
+        let project_payees = self
+            .get_project_payees_internal()
+            .into_iter()
+            .map(|(payee, _)| {
+                (
+                    payee,
+                    self.data::<Data>()
+                        .withdrawn_sales
+                        .get(payee)
+                        .unwrap_or_default(),
+                    self.get_available_to_withdraw_payee_internal(payee),
+                )
+            })
+            .collect();
+
+        FinancialSummary {
+            total_sales: self.data::<Data>().total_sales,
+            total_refund: self.data::<Data>().total_refund,
+            withdrawn_launchpad,
+            withdrawn_project: self.get_withdrawn_sales_project_internal(),
+            available_to_withdraw_launchpad: self.get_available_to_withdraw_launchpad_internal(),
+            available_to_withdraw_project: self.get_available_to_withdraw_project_internal(),
+            project_payees,
+            refund_liability: self.get_refund_liability_internal(),
+            total_pending_credits: self
+                .data::<Data>()
+                .total_pending_credits
+                .get()
+                .unwrap_or_default(),
+            contract_balance: Self::env().balance(),
+        }
+    }
+
+    default fn get_available_to_withdraw_payee(&self, account_id: AccountId) -> Balance {
+        self.get_available_to_withdraw_payee_internal(account_id)
+    }
+
+    default fn get_withdrawn_sales(&self, account_id: AccountId) -> Balance {
+        self.data::<Data>()
+            .withdrawn_sales
+            .get(account_id)
+            .unwrap_or_default()
+    }
+
+    #[modifiers(only_owner)]
+    default fn set_project_payees(
+        &mut self,
+        payees: Vec<(AccountId, BasisPoints)>,
+    ) -> Result<(), PSP34Error> {
+        let launchpad_treasury = self.data::<Data>().launchpad_treasury;
+        let mut total_basis_points: u32 = 0;
+        for (index, (payee, basis_points)) in payees.iter().enumerate() {
+            if *basis_points == 0
+                || Some(*payee) == launchpad_treasury
+                || payees[..index].iter().any(|(other, _)| other == payee)
+            {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::InvalidInput.as_str(),
+                )));
+            }
+            total_basis_points += *basis_points as u32;
+        }
+        if total_basis_points != MAX_BASIS_POINTS as u32 {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::InvalidInput.as_str(),
+            )));
+        }
+        // shares are fixed once a payee withdrew, the ledger would no longer add up
+        if self.get_withdrawn_sales_project_internal() > 0 {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::PayeesLocked.as_str(),
+            )));
+        }
+
+        self.data::<Data>().project_payees.set(&payees);
+        Ok(())
+    }
+
+    default fn get_project_payees(&self) -> Vec<(AccountId, BasisPoints)> {
+        self.get_project_payees_internal()
+    }
+
     default fn refund(&mut self, token_id: u64) -> Result<u128, PSP34Error> {
         let caller_id = Self::env().caller();
 
@@ -194,36 +476,39 @@ where
                 Shiden34Error::RefundFailed.as_str(),
             )));
         } else {
+            let refund_amount =
+                self.check_refund_eligibility(token_id, caller_id, refund_amount)?;
             let refund_address = self.data::<Data>().refund_address.unwrap();
-            let res = self._transfer_token(refund_address, Id::U64(token_id), Vec::new());
-            match res {
-                Ok(_) => {
-                    self.data::<Data>().minting_type_for_token.remove(token_id);
-
-                    Self::env()
-                        .transfer(caller_id, refund_amount)
-                        .map_err(|_| {
-                            PSP34Error::Custom(String::from(
-                                Shiden34Error::WithdrawalFailed.as_str(),
-                            ))
-                        })?;
-                    self._emit_refund_event(
-                        caller_id,
-                        refund_address,
-                        Some(Id::U64(token_id)),
-                        price,
-                        refund_amount,
-                    );
-                    self.data::<Data>().total_refund += refund_amount;
-                }
-                _ => (),
-            };
+            self._transfer_token(refund_address, Id::U64(token_id), Vec::new())?;
+
+            self.data::<Data>().minting_type_for_token.remove(token_id);
+            self.data::<Data>().original_minter.remove(token_id);
+            self.data::<Data>().last_sale_price.remove(token_id);
+            self.data::<Data>().total_refund += refund_amount;
+            let refundable_sales = self
+                .data::<Data>()
+                .refundable_sales
+                .get()
+                .unwrap_or_default();
+            self.data::<Data>()
+                .refundable_sales
+                .set(&(refundable_sales - price));
+
+            // if the holder can't accept the transfer the refund is kept as a claimable credit
+            self.pay_or_credit(caller_id, refund_amount);
+            self._emit_refund_event(
+                caller_id,
+                refund_address,
+                Some(Id::U64(token_id)),
+                price,
+                refund_amount,
+            );
             return Ok(refund_amount);
         }
     }
 
     /// Set max number of tokens which could be minted per call
-    #[modifiers(only_owner)]
+    #[modifiers(only_role(PHASE_ADMIN))]
     default fn set_max_mint_amount(&mut self, max_amount: u64) -> Result<(), PSP34Error> {
         self.data::<Data>().max_amount = max_amount;
 
@@ -248,7 +533,39 @@ where
         self.get_refund_amount_and_price_internal(token_id).0
     }
 
-    #[modifiers(only_owner)]
+    default fn get_refund_amount_at(&self, token_id: u64, timestamp: u64) -> Balance {
+        self.get_refund_amount_and_price_at_internal(token_id, timestamp)
+            .0
+    }
+
+    default fn get_refund_schedule(&self, token_id: u64) -> Vec<(u64, Balance)> {
+        let current_timestamp = Self::env().block_timestamp();
+        let mut schedule: Vec<(u64, Balance)> = Vec::new();
+        let mut last_deadline: u64 = 0;
+
+        for refund_period in self.data::<Data>().refund_periods.iter() {
+            let deadline = self
+                .data::<Data>()
+                .public_sale_end_at
+                .saturating_add(*refund_period);
+            // periods ending before a previous one are never reached
+            if deadline <= last_deadline {
+                continue;
+            }
+            last_deadline = deadline;
+
+            if current_timestamp < deadline {
+                let (refund_amount, _) =
+                    self.get_refund_amount_and_price_at_internal(token_id, deadline - 1);
+                if refund_amount > 0 {
+                    schedule.push((deadline, refund_amount));
+                }
+            }
+        }
+        schedule
+    }
+
+    #[modifiers(only_role(WHITELIST_MANAGER))]
     default fn add_account_to_prepresale(
         &mut self,
         account_id: AccountId,
@@ -260,7 +577,7 @@ where
         Ok(())
     }
 
-    #[modifiers(only_owner)]
+    #[modifiers(only_role(WHITELIST_MANAGER))]
     fn add_account_to_prepresale_batch(
         &mut self,
         account_id_mint_amounts: Vec<(AccountId, u64)>,
@@ -273,7 +590,7 @@ where
         Ok(())
     }
 
-    #[modifiers(only_owner)]
+    #[modifiers(only_role(WHITELIST_MANAGER))]
     fn add_account_to_presale_batch(
         &mut self,
         account_id_mint_amounts: Vec<(AccountId, u64)>,
@@ -286,7 +603,7 @@ where
         Ok(())
     }
 
-    #[modifiers(only_owner)]
+    #[modifiers(only_role(WHITELIST_MANAGER))]
     default fn add_account_to_presale(
         &mut self,
         account_id: AccountId,
@@ -298,11 +615,12 @@ where
         Ok(())
     }
 
-    #[modifiers(only_owner)]
+    #[modifiers(only_role(PHASE_ADMIN))]
     default fn set_minting_status(
         &mut self,
         minting_status_index: Option<u8>,
     ) -> Result<(), PSP34Error> {
+        self._check_timelock(TimelockOperation::SetMintingStatus(minting_status_index))?;
         self.data::<Data>().forced_minting_status = minting_status_index;
         return Ok(());
     }
@@ -312,14 +630,507 @@ where
         &mut self,
         refund_periods: Vec<MilliSeconds>,
     ) -> Result<(), PSP34Error> {
-        if self.data::<Data>().refund_periods.len() != refund_periods.len() {
+        if self.data::<Data>().refund_periods.len() != refund_periods.len()
+            || refund_periods
+                .iter()
+                .any(|refund_period| *refund_period > MAX_REFUND_PERIOD)
+        {
             return Err(PSP34Error::Custom(String::from("InvalidInput")));
         }
+        if self.data::<Data>().total_sales > 0
+            && self
+                .data::<Data>()
+                .refund_periods
+                .iter()
+                .zip(refund_periods.iter())
+                .any(|(current_period, new_period)| new_period < current_period)
+        {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::RefundPeriodShortened.as_str(),
+            )));
+        }
+        let refund_shares = self.data::<Data>().refund_shares.clone();
+        self.check_refund_liability_covered(&refund_periods, &refund_shares)?;
+        self._check_timelock(TimelockOperation::SetRefundPeriods(refund_periods.clone()))?;
 
         self.data::<Data>().refund_periods = refund_periods;
         Ok(())
     }
 
+    #[modifiers(only_owner)]
+    default fn set_refund_shares(
+        &mut self,
+        refund_shares: Vec<Percentage>,
+    ) -> Result<(), PSP34Error> {
+        if self.data::<Data>().refund_shares.len() != refund_shares.len()
+            || refund_shares
+                .iter()
+                .any(|refund_share| *refund_share >= 100)
+        {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::InvalidInput.as_str(),
+            )));
+        }
+        if self.data::<Data>().total_sales > 0
+            && self
+                .data::<Data>()
+                .refund_shares
+                .iter()
+                .zip(refund_shares.iter())
+                .any(|(current_share, new_share)| new_share < current_share)
+        {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::RefundShareDecreased.as_str(),
+            )));
+        }
+        let refund_periods = self.data::<Data>().refund_periods.clone();
+        self.check_refund_liability_covered(&refund_periods, &refund_shares)?;
+
+        self.data::<Data>().refund_shares = refund_shares;
+        Ok(())
+    }
+
+    #[modifiers(only_owner)]
+    default fn set_refund_policy(&mut self, refund_policy_index: u8) -> Result<(), PSP34Error> {
+        if RefundPolicy::from(refund_policy_index).to_index() != refund_policy_index {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::InvalidInput.as_str(),
+            )));
+        }
+        // buyers minted under the announced policy
+        if self.data::<Data>().total_sales > 0 {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::RefundPolicyLocked.as_str(),
+            )));
+        }
+
+        self.data::<Data>().refund_policy.set(&refund_policy_index);
+        Ok(())
+    }
+
+    default fn get_refund_policy(&self) -> u8 {
+        self.data::<Data>().refund_policy.get().unwrap_or_default()
+    }
+
+    default fn get_original_minter(&self, token_id: u64) -> Option<AccountId> {
+        self.data::<Data>().original_minter.get(token_id)
+    }
+
+    default fn get_last_sale_price(&self, token_id: u64) -> Balance {
+        self.data::<Data>()
+            .last_sale_price
+            .get(token_id)
+            .unwrap_or(0)
+    }
+
+    #[modifiers(only_owner)]
+    default fn set_default_vote_config(
+        &mut self,
+        quorum: Percentage,
+        threshold: Percentage,
+        voting_period: MilliSeconds,
+    ) -> Result<(), PSP34Error> {
+        if quorum >= 100 || threshold == 0 || threshold > 100 || voting_period == 0 {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::InvalidInput.as_str(),
+            )));
+        }
+        // buyers minted under the announced vote rules
+        if self.data::<Data>().total_sales > 0 {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::DefaultVoteConfigLocked.as_str(),
+            )));
+        }
+        if self.get_default_proposal().is_some() {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::DefaultProposalActive.as_str(),
+            )));
+        }
+
+        self.data::<Data>().default_quorum.set(&quorum);
+        self.data::<Data>().default_threshold.set(&threshold);
+        self.data::<Data>()
+            .default_voting_period
+            .set(&voting_period);
+        Ok(())
+    }
+
+    default fn get_default_vote_config(&self) -> (Percentage, Percentage, MilliSeconds) {
+        (
+            self.data::<Data>().default_quorum.get().unwrap_or_default(),
+            self.data::<Data>()
+                .default_threshold
+                .get()
+                .unwrap_or_default(),
+            self.data::<Data>()
+                .default_voting_period
+                .get()
+                .unwrap_or_default(),
+        )
+    }
+
+    default fn propose_default(&mut self) -> Result<u32, PSP34Error> {
+        if self.is_project_defaulted() {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::ProjectDefaulted.as_str(),
+            )));
+        }
+        if self.get_default_proposal().is_some() {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::DefaultProposalActive.as_str(),
+            )));
+        }
+        let (_, _, default_voting_period) = self.get_default_vote_config();
+        if default_voting_period == 0 || self.get_current_minting_status() != MintingStatus::End {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::DefaultVotingNotOpen.as_str(),
+            )));
+        }
+
+        let current_timestamp = Self::env().block_timestamp();
+        let end_at = current_timestamp.saturating_add(default_voting_period);
+        let proposal_id = self
+            .data::<Data>()
+            .default_proposal_count
+            .get()
+            .unwrap_or_default()
+            + 1;
+        self.data::<Data>().default_proposal_count.set(&proposal_id);
+        self.data::<Data>()
+            .default_proposal
+            .set(&Some(DefaultProposal {
+                id: proposal_id,
+                proposer: Self::env().caller(),
+                start_at: current_timestamp,
+                end_at,
+                votes_for: 0,
+                votes_against: 0,
+            }));
+        Ok(proposal_id)
+    }
+
+    default fn vote_default(
+        &mut self,
+        token_ids: Vec<u64>,
+        support: bool,
+    ) -> Result<(), PSP34Error> {
+        let caller_id = Self::env().caller();
+        let current_timestamp = Self::env().block_timestamp();
+
+        let mut proposal = match self.get_default_proposal() {
+            Some(proposal) if current_timestamp < proposal.end_at => proposal,
+            _ => {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::DefaultVotingNotOpen.as_str(),
+                )))
+            }
+        };
+
+        for token_id in token_ids {
+            if self._owner_of(&Id::U64(token_id)) != Some(caller_id) {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::Unauthorized.as_str(),
+                )));
+            }
+            if self
+                .data::<Data>()
+                .default_votes
+                .get((proposal.id, token_id))
+                .is_some()
+            {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::AlreadyVoted.as_str(),
+                )));
+            }
+
+            // only tokens still holding escrow have a say
+            let minting_type_index = self.data::<Data>().minting_type_for_token.get(token_id);
+            if minting_type_index.is_none() {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::RefundNotEligible.as_str(),
+                )));
+            }
+            let weight = self.get_price_internal(minting_type_index.unwrap());
+
+            if support {
+                proposal.votes_for += weight;
+            } else {
+                proposal.votes_against += weight;
+            }
+            self.data::<Data>()
+                .default_votes
+                .insert((proposal.id, token_id), &support);
+        }
+
+        self.data::<Data>().default_proposal.set(&Some(proposal));
+        Ok(())
+    }
+
+    default fn finalize_default(&mut self) -> Result<bool, PSP34Error> {
+        let current_timestamp = Self::env().block_timestamp();
+
+        let proposal = match self.get_default_proposal() {
+            Some(proposal) => proposal,
+            None => {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::DefaultVotingNotOpen.as_str(),
+                )))
+            }
+        };
+        if current_timestamp < proposal.end_at {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::DefaultVotingNotEnded.as_str(),
+            )));
+        }
+
+        let (default_quorum, default_threshold, _) = self.get_default_vote_config();
+        let refundable_sales = self
+            .data::<Data>()
+            .refundable_sales
+            .get()
+            .unwrap_or_default();
+        let total_votes = proposal.votes_for + proposal.votes_against;
+        let quorum_reached = total_votes * 100 >= default_quorum * refundable_sales;
+        let threshold_reached =
+            total_votes > 0 && proposal.votes_for * 100 >= default_threshold * total_votes;
+
+        self.data::<Data>().default_proposal.set(&None);
+
+        if quorum_reached && threshold_reached {
+            let refund_ratio = self.declare_default_internal();
+            self._emit_project_defaulted_event(Some(proposal.id), refund_ratio);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    default fn get_default_proposal(&self) -> Option<DefaultProposal> {
+        self.data::<Data>().default_proposal.get().flatten()
+    }
+
+    default fn is_project_defaulted(&self) -> bool {
+        self.data::<Data>()
+            .project_defaulted
+            .get()
+            .unwrap_or_default()
+    }
+
+    #[modifiers(only_owner)]
+    default fn set_milestones(
+        &mut self,
+        milestone_shares: Vec<Percentage>,
+    ) -> Result<(), PSP34Error> {
+        if !milestone_shares.is_empty() && milestone_shares.iter().sum::<Percentage>() != 100 {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::InvalidInput.as_str(),
+            )));
+        }
+        // buyers minted under the announced tranches
+        if self.data::<Data>().total_sales > 0
+            || self.get_milestones().iter().any(|(_, approved)| *approved)
+        {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::MilestonesLocked.as_str(),
+            )));
+        }
+
+        let milestones: Vec<(Percentage, bool)> = milestone_shares
+            .into_iter()
+            .map(|milestone_share| (milestone_share, false))
+            .collect();
+        self.data::<Data>().milestones.set(&milestones);
+        Ok(())
+    }
+
+    #[modifiers(only_owner)]
+    default fn set_milestone_arbiter(
+        &mut self,
+        arbiter: Option<AccountId>,
+    ) -> Result<(), PSP34Error> {
+        self.data::<Data>().milestone_arbiter.set(&arbiter);
+        Ok(())
+    }
+
+    default fn approve_milestone(&mut self, index: u32) -> Result<(), PSP34Error> {
+        let caller_id = Self::env().caller();
+        self.check_milestone_approver(caller_id)?;
+
+        if self.is_project_defaulted() {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::ProjectDefaulted.as_str(),
+            )));
+        }
+
+        let mut milestones = self.get_milestones();
+        match milestones.get_mut(index as usize) {
+            Some((_, approved)) if !*approved => *approved = true,
+            Some(_) => {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::MilestoneAlreadyApproved.as_str(),
+                )))
+            }
+            None => {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::InvalidInput.as_str(),
+                )))
+            }
+        }
+        self.data::<Data>().milestones.set(&milestones);
+
+        self._emit_milestone_approved_event(index, caller_id);
+        Ok(())
+    }
+
+    default fn declare_project_failed(&mut self) -> Result<(), PSP34Error> {
+        self.check_milestone_approver(Self::env().caller())?;
+
+        if self.get_milestones().is_empty() {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::InvalidInput.as_str(),
+            )));
+        }
+        if self.is_project_defaulted() {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::ProjectDefaulted.as_str(),
+            )));
+        }
+        if self.get_current_minting_status() != MintingStatus::End {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::MintingNotEnded.as_str(),
+            )));
+        }
+
+        let refund_ratio = self.declare_default_internal();
+        self._emit_project_defaulted_event(None, refund_ratio);
+        Ok(())
+    }
+
+    default fn get_milestones(&self) -> Vec<(Percentage, bool)> {
+        self.data::<Data>().milestones.get().unwrap_or_default()
+    }
+
+    default fn get_milestone_arbiter(&self) -> Option<AccountId> {
+        self.data::<Data>().milestone_arbiter.get().flatten()
+    }
+
+    #[modifiers(only_owner)]
+    default fn set_vesting_schedule(
+        &mut self,
+        cliff: MilliSeconds,
+        duration: MilliSeconds,
+    ) -> Result<(), PSP34Error> {
+        if cliff > duration {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::InvalidInput.as_str(),
+            )));
+        }
+        // buyers minted under the announced schedule
+        if self.data::<Data>().total_sales > 0 {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::VestingLocked.as_str(),
+            )));
+        }
+
+        self.data::<Data>().vesting_cliff.set(&cliff);
+        self.data::<Data>().vesting_duration.set(&duration);
+        Ok(())
+    }
+
+    default fn get_vesting_schedule(&self) -> (MilliSeconds, MilliSeconds) {
+        (
+            self.data::<Data>().vesting_cliff.get().unwrap_or_default(),
+            self.data::<Data>()
+                .vesting_duration
+                .get()
+                .unwrap_or_default(),
+        )
+    }
+
+    default fn get_vested_project_share(&self) -> Balance {
+        self.get_project_share_internal()
+    }
+
+    default fn get_withdrawn_project_share(&self) -> Balance {
+        self.get_withdrawn_sales_project_internal()
+    }
+
+    default fn get_locked_project_share(&self) -> Balance {
+        self.get_earned_project_share_internal()
+            .saturating_sub(self.get_project_share_internal())
+    }
+
+    default fn propose_address_change(
+        &mut self,
+        address_kind_index: AddressKindIndex,
+        new_address: AccountId,
+    ) -> Result<(), PSP34Error> {
+        let caller_id = Self::env().caller();
+        let address_kind = AddressKind::from(address_kind_index).ok_or(PSP34Error::Custom(
+            String::from(Shiden34Error::InvalidInput.as_str()),
+        ))?;
+        let current_address = self.get_address_internal(&address_kind);
+
+        // the owner may not move the launchpad fee away from the launchpad
+        let is_owner = caller_id == self.data::<ownable::Data>().owner
+            && address_kind != AddressKind::LaunchpadTreasury;
+        if Some(caller_id) != current_address && !is_owner {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::Unauthorized.as_str(),
+            )));
+        }
+
+        self.check_address_available(&address_kind, new_address)?;
+
+        self.data::<Data>()
+            .pending_address_changes
+            .insert(address_kind_index, &new_address);
+        self._emit_address_change_proposed_event(address_kind_index, caller_id, new_address);
+        Ok(())
+    }
+
+    default fn accept_address_change(
+        &mut self,
+        address_kind_index: AddressKindIndex,
+    ) -> Result<(), PSP34Error> {
+        let caller_id = Self::env().caller();
+        let address_kind = AddressKind::from(address_kind_index).ok_or(PSP34Error::Custom(
+            String::from(Shiden34Error::InvalidInput.as_str()),
+        ))?;
+        let pending_address = self
+            .data::<Data>()
+            .pending_address_changes
+            .get(address_kind_index)
+            .ok_or(PSP34Error::Custom(String::from(
+                Shiden34Error::NoPendingAddressChange.as_str(),
+            )))?;
+
+        if caller_id != pending_address {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::Unauthorized.as_str(),
+            )));
+        }
+
+        // payees or the other treasury may have taken the address since it was proposed
+        self.check_address_available(&address_kind, pending_address)?;
+
+        let old_address = self.get_address_internal(&address_kind);
+        self.set_address_internal(&address_kind, pending_address);
+        self.data::<Data>()
+            .pending_address_changes
+            .remove(address_kind_index);
+        self._emit_address_changed_event(address_kind_index, old_address, pending_address);
+        Ok(())
+    }
+
+    default fn get_pending_address_change(
+        &self,
+        address_kind_index: AddressKindIndex,
+    ) -> Option<AccountId> {
+        self.data::<Data>()
+            .pending_address_changes
+            .get(address_kind_index)
+    }
+
     default fn get_minting_status(&self) -> String {
         let minting_status = self.get_current_minting_status();
         match minting_status {
@@ -331,13 +1142,48 @@ where
         }
     }
 
-    default fn _emit_refund_event(
+    default fn _emit_refund_event(
+        &self,
+        _from: AccountId,
+        _to: AccountId,
+        _id: Option<Id>,
+        _price: Balance,
+        _refunded: Balance,
+    ) {
+    }
+
+    default fn _emit_milestone_approved_event(&self, _index: u32, _approver: AccountId) {}
+
+    default fn _emit_withdrawn_event(
+        &self,
+        _payer: AccountId,
+        _destination: AccountId,
+        _amount: Balance,
+    ) {
+    }
+
+    default fn _emit_address_change_proposed_event(
         &self,
-        _from: AccountId,
-        _to: AccountId,
-        _id: Option<Id>,
-        _price: Balance,
-        _refunded: Balance,
+        _address_kind_index: AddressKindIndex,
+        _proposer: AccountId,
+        _new_address: AccountId,
+    ) {
+    }
+
+    default fn _emit_address_changed_event(
+        &self,
+        _address_kind_index: AddressKindIndex,
+        _old_address: Option<AccountId>,
+        _new_address: AccountId,
+    ) {
+    }
+
+    default fn _emit_refund_waived_event(&self, _holder: AccountId, _token_id: u64) {}
+
+    default fn _emit_project_defaulted_event(
+        &self,
+        _proposal_id: Option<u32>,
+        _refund_ratio: u128,
     ) {
     }
 
@@ -387,6 +1233,152 @@ where
         self.data::<Data>().refund_shares.to_vec()
     }
 
+    /// Set the URI of the collection-level metadata read by marketplaces
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn set_contract_uri(&mut self, uri: PreludeString) -> Result<(), PSP34Error> {
+        self.set_collection_attribute_internal(CONTRACT_URI_ATTRIBUTE, uri)
+    }
+
+    default fn contract_uri(&self) -> Option<PreludeString> {
+        self.get_collection_attribute_internal(CONTRACT_URI_ATTRIBUTE)
+    }
+
+    /// Set a collection attribute, an empty value removes it
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn set_collection_attribute(
+        &mut self,
+        attribute_index: CollectionAttributeIndex,
+        value: PreludeString,
+    ) -> Result<(), PSP34Error> {
+        let attribute = CollectionAttribute::from(attribute_index).ok_or(PSP34Error::Custom(
+            String::from(Shiden34Error::InvalidInput.as_str()),
+        ))?;
+        self.set_collection_attribute_internal(attribute.as_key(), value)
+    }
+
+    default fn get_collection_attribute(
+        &self,
+        attribute_index: CollectionAttributeIndex,
+    ) -> Option<PreludeString> {
+        let attribute = CollectionAttribute::from(attribute_index)?;
+        self.get_collection_attribute_internal(attribute.as_key())
+    }
+
+    /// Block transfers of tokens while they are refundable so they can't be resold and refunded by the buyer
+    #[modifiers(only_owner)]
+    default fn set_transfer_lock(&mut self, enabled: bool) -> Result<(), PSP34Error> {
+        // buyers minted knowing whether they could resell
+        if self.data::<Data>().total_sales > 0 {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::TransferLockLocked.as_str(),
+            )));
+        }
+        self.data::<Data>().transfer_lock.set(&enabled);
+        Ok(())
+    }
+
+    default fn get_transfer_lock(&self) -> bool {
+        self.data::<Data>().transfer_lock.get().unwrap_or_default()
+    }
+
+    default fn is_transferable(&self, token_id: u64) -> bool {
+        !self.get_transfer_lock() || !self.is_refundable_internal(token_id)
+    }
+
+    /// Give up the refund of a token, which makes it transferable while the transfer lock is set
+    default fn waive_refund(&mut self, token_id: u64) -> Result<(), PSP34Error> {
+        let caller_id = Self::env().caller();
+        if Some(caller_id) != self._owner_of(&Id::U64(token_id)) {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::Unauthorized.as_str(),
+            )));
+        }
+        self.check_migrated()?;
+        let minting_type_index = self
+            .data::<Data>()
+            .minting_type_for_token
+            .get(token_id)
+            .ok_or(PSP34Error::Custom(String::from(
+                Shiden34Error::TokenNotRefundable.as_str(),
+            )))?;
+
+        let price = self.get_price_internal(minting_type_index);
+        self.data::<Data>().minting_type_for_token.remove(token_id);
+        self.data::<Data>().original_minter.remove(token_id);
+        self.data::<Data>().last_sale_price.remove(token_id);
+        let refundable_sales = self
+            .data::<Data>()
+            .refundable_sales
+            .get()
+            .unwrap_or_default();
+        self.data::<Data>()
+            .refundable_sales
+            .set(&(refundable_sales - price));
+
+        self._emit_refund_waived_event(caller_id, token_id);
+        Ok(())
+    }
+
+    #[modifiers(only_role(MARKETPLACE))]
+    default fn transfer_sold(
+        &mut self,
+        to: AccountId,
+        token_id: u64,
+        price: Balance,
+    ) -> Result<(), PSP34Error> {
+        self.data::<Data>()
+            .pending_sale_price
+            .insert(token_id, &price);
+        let result = self._transfer_token(to, Id::U64(token_id), Vec::new());
+        self.data::<Data>().pending_sale_price.remove(token_id);
+        result
+    }
+
+    default fn _check_transfer_allowed(
+        &self,
+        from: Option<&AccountId>,
+        to: Option<&AccountId>,
+        id: &Id,
+    ) -> Result<(), PSP34Error> {
+        // mints, burns and refunds are never blocked
+        if from.is_none() || to.is_none() || to == self.data::<Data>().refund_address.as_ref() {
+            return Ok(());
+        }
+        if let Id::U64(token_id) = id {
+            if !self.is_transferable(*token_id) {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::TransferLocked.as_str(),
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    default fn _record_transfer_price(
+        &mut self,
+        from: Option<&AccountId>,
+        to: Option<&AccountId>,
+        id: &Id,
+    ) {
+        // mints and refunds track their prices themselves
+        if from.is_none() || to.is_none() || to == self.data::<Data>().refund_address.as_ref() {
+            return;
+        }
+        if let Id::U64(token_id) = id {
+            if !self.data::<Data>().last_sale_price.contains(token_id) {
+                return;
+            }
+            // a transfer no marketplace reported was not paid for
+            let price = self
+                .data::<Data>()
+                .pending_sale_price
+                .get(token_id)
+                .unwrap_or(0);
+            self.data::<Data>().pending_sale_price.remove(token_id);
+            self.data::<Data>().last_sale_price.insert(token_id, &price);
+        }
+    }
+
     default fn get_refund_address(&self) -> AccountId {
         self.data::<Data>().refund_address.unwrap()
     }
@@ -407,7 +1399,7 @@ where
 /// Helper trait for Launchpad
 impl<T> Internal for T
 where
-    T: Storage<Data> + Storage<psp34::Data<enumerable::Balances>>,
+    T: Storage<Data> + Storage<psp34::Data<enumerable::Balances>> + Storage<metadata::Data>,
 {
     /// Check if the transferred mint values is as expected
     default fn check_value(
@@ -543,35 +1535,98 @@ where
             return (0, 0);
         }
 
+        let current_timestamp = Self::env().block_timestamp();
+        self.get_refund_amount_and_price_at_internal(token_id, current_timestamp)
+    }
+
+    default fn get_refund_amount_and_price_at_internal(
+        &self,
+        token_id: u64,
+        timestamp: u64,
+    ) -> (Balance, Balance) {
         let minting_type_index = self.data::<Data>().minting_type_for_token.get(token_id);
         if minting_type_index.is_none() {
             return (0, 0);
         }
-        let current_timestamp = Self::env().block_timestamp();
 
-        let price: u128 = if minting_type_index.unwrap() == 1 {
+        let price: u128 = self.get_price_internal(minting_type_index.unwrap());
+
+        if self
+            .data::<Data>()
+            .project_defaulted
+            .get()
+            .unwrap_or_default()
+        {
+            let default_refund_ratio = self
+                .data::<Data>()
+                .default_refund_ratio
+                .get()
+                .unwrap_or_default();
+            let refund_amount: Balance =
+                (price * default_refund_ratio).saturating_div(DEFAULT_REFUND_RATIO_PRECISION);
+
+            return (refund_amount, price);
+        }
+
+        if let Some(refund_share) = self.get_refund_share_at_internal(timestamp) {
+            let refund_amount: Balance = (price * refund_share).saturating_div(100); // TO DO: check accuracy
+
+            return (refund_amount, price);
+        }
+
+        return (0, 0);
+    }
+
+    default fn get_price_internal(&self, minting_type_index: MintingStatusIndex) -> Balance {
+        if minting_type_index == 1 {
             self.data::<Data>().prepresale_price_per_mint
-        } else if minting_type_index.unwrap() == 2 {
+        } else if minting_type_index == 2 {
             self.data::<Data>().presale_price_per_mint
         } else {
             self.data::<Data>().price_per_mint
-        };
-
-        for (i, refund_period) in self.data::<Data>().refund_periods.iter().enumerate() {
-            if current_timestamp < (self.data::<Data>().public_sale_end_at + refund_period) {
-                let refund_share: Balance =
-                    *self.data::<Data>().refund_shares.get(i).unwrap_or(&100);
+        }
+    }
 
-                let refund_amount: Balance = (price * refund_share).saturating_div(100); // TO DO: check accuracy
+    default fn check_refund_eligibility(
+        &self,
+        token_id: u64,
+        account_id: AccountId,
+        refund_amount: Balance,
+    ) -> Result<Balance, PSP34Error> {
+        if self.data::<Data>().original_minter.get(token_id) == Some(account_id) {
+            return Ok(refund_amount);
+        }
 
-                return (refund_amount, price);
+        match RefundPolicy::from(self.data::<Data>().refund_policy.get().unwrap_or_default()) {
+            RefundPolicy::AnyHolder => Ok(refund_amount),
+            RefundPolicy::OriginalMinterOnly => Err(PSP34Error::Custom(String::from(
+                Shiden34Error::RefundNotEligible.as_str(),
+            ))),
+            RefundPolicy::LastSalePriceCapped => {
+                let last_sale_price = self
+                    .data::<Data>()
+                    .last_sale_price
+                    .get(token_id)
+                    .unwrap_or(0);
+                if last_sale_price == 0 {
+                    return Err(PSP34Error::Custom(String::from(
+                        Shiden34Error::RefundNotEligible.as_str(),
+                    )));
+                }
+                Ok(refund_amount.min(last_sale_price))
             }
         }
-
-        return (0, 0);
     }
 
     default fn get_current_minting_status(&self) -> MintingStatus {
+        if self
+            .data::<Data>()
+            .project_defaulted
+            .get()
+            .unwrap_or_default()
+        {
+            return MintingStatus::End;
+        }
         if let Some(minting_status) = self.data::<Data>().forced_minting_status {
             return MintingStatus::from(minting_status);
         }
@@ -595,30 +1650,449 @@ where
             return MintingStatus::Closed;
         }
     }
+
+    default fn get_refund_share_at_internal(&self, timestamp: u64) -> Option<Percentage> {
+        for (i, refund_period) in self.data::<Data>().refund_periods.iter().enumerate() {
+            if timestamp
+                < self
+                    .data::<Data>()
+                    .public_sale_end_at
+                    .saturating_add(*refund_period)
+            {
+                return Some(*self.data::<Data>().refund_shares.get(i).unwrap_or(&100));
+            }
+        }
+        None
+    }
+
+    default fn get_refund_liability_internal(&self) -> Balance {
+        let refundable_sales = self
+            .data::<Data>()
+            .refundable_sales
+            .get()
+            .unwrap_or_default();
+        if self
+            .data::<Data>()
+            .project_defaulted
+            .get()
+            .unwrap_or_default()
+        {
+            let default_refund_ratio = self
+                .data::<Data>()
+                .default_refund_ratio
+                .get()
+                .unwrap_or_default();
+            return (refundable_sales * default_refund_ratio)
+                .saturating_div(DEFAULT_REFUND_RATIO_PRECISION);
+        }
+
+        let current_timestamp = Self::env().block_timestamp();
+        let refund_share = self
+            .get_refund_share_at_internal(current_timestamp)
+            .unwrap_or(0);
+
+        (refundable_sales * refund_share).saturating_div(100)
+    }
+
+    default fn check_refund_liability_covered(
+        &self,
+        refund_periods: &[MilliSeconds],
+        refund_shares: &[Percentage],
+    ) -> Result<(), PSP34Error> {
+        if self
+            .data::<Data>()
+            .project_defaulted
+            .get()
+            .unwrap_or_default()
+        {
+            return Ok(());
+        }
+
+        // extending a period or raising a share may reopen refunds of sales already paid out
+        let current_timestamp = Self::env().block_timestamp();
+        let public_sale_end_at = self.data::<Data>().public_sale_end_at;
+        let highest_refund_share = refund_periods
+            .iter()
+            .zip(refund_shares.iter())
+            .filter(|(refund_period, _)| {
+                current_timestamp < public_sale_end_at.saturating_add(**refund_period)
+            })
+            .map(|(_, refund_share)| *refund_share)
+            .max()
+            .unwrap_or(0);
+        let refund_liability = (self
+            .data::<Data>()
+            .refundable_sales
+            .get()
+            .unwrap_or_default()
+            * highest_refund_share)
+            .saturating_div(100);
+
+        let available_balance = Self::env()
+            .balance()
+            .saturating_sub(Self::env().minimum_balance())
+            .saturating_sub(
+                self.data::<Data>()
+                    .total_pending_credits
+                    .get()
+                    .unwrap_or_default(),
+            );
+        if refund_liability > available_balance {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::RefundLiabilityUncovered.as_str(),
+            )));
+        }
+        Ok(())
+    }
+
+    default fn get_excess_balance_internal(&self) -> Balance {
+        Self::env()
+            .balance()
+            .saturating_sub(Self::env().minimum_balance())
+            .saturating_sub(
+                self.data::<Data>()
+                    .total_pending_credits
+                    .get()
+                    .unwrap_or_default(),
+            )
+            .saturating_sub(self.get_refund_liability_internal())
+    }
+
+    default fn check_milestone_approver(&self, account_id: AccountId) -> Result<(), PSP34Error> {
+        if Some(account_id) != self.data::<Data>().launchpad_treasury
+            && Some(account_id) != self.data::<Data>().milestone_arbiter.get().flatten()
+        {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::Unauthorized.as_str(),
+            )));
+        }
+        Ok(())
+    }
+
+    default fn declare_default_internal(&mut self) -> u128 {
+        // tranches of milestones approved so far stay with the project
+        let released_project_share = if self
+            .data::<Data>()
+            .milestones
+            .get()
+            .unwrap_or_default()
+            .is_empty()
+        {
+            0
+        } else {
+            self.get_earned_project_share_internal()
+        };
+        self.data::<Data>()
+            .default_project_share
+            .set(&released_project_share);
+
+        // the launchpad keeps what it earned so far, everything else goes back to holders
+        let refund_pool = Self::env()
+            .balance()
+            .saturating_sub(Self::env().minimum_balance())
+            .saturating_sub(
+                self.data::<Data>()
+                    .total_pending_credits
+                    .get()
+                    .unwrap_or_default(),
+            )
+            .saturating_sub(self.get_available_to_withdraw_launchpad_internal())
+            .saturating_sub(
+                released_project_share.saturating_sub(self.get_withdrawn_sales_project_internal()),
+            );
+        let refundable_sales = self
+            .data::<Data>()
+            .refundable_sales
+            .get()
+            .unwrap_or_default();
+
+        let refund_ratio = if refundable_sales == 0 {
+            0
+        } else {
+            (refund_pool * DEFAULT_REFUND_RATIO_PRECISION).saturating_div(refundable_sales)
+        };
+
+        self.data::<Data>().default_refund_ratio.set(&refund_ratio);
+        self.data::<Data>().project_defaulted.set(&true);
+        refund_ratio
+    }
+
+    default fn pay_or_credit(&mut self, account_id: AccountId, amount: Balance) {
+        self.pay_to_or_credit(account_id, account_id, amount);
+    }
+
+    default fn pay_to_or_credit(
+        &mut self,
+        destination: AccountId,
+        account_id: AccountId,
+        amount: Balance,
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        if Self::env().transfer(destination, amount).is_err() {
+            let pending_credit = self
+                .data::<Data>()
+                .pending_credits
+                .get(account_id)
+                .unwrap_or(0);
+            self.data::<Data>()
+                .pending_credits
+                .insert(account_id, &(pending_credit + amount));
+            let total_pending_credits = self
+                .data::<Data>()
+                .total_pending_credits
+                .get()
+                .unwrap_or_default();
+            self.data::<Data>()
+                .total_pending_credits
+                .set(&(total_pending_credits + amount));
+        }
+    }
+
     fn get_available_to_withdraw_launchpad_internal(&self) -> Balance {
+        match self.data::<Data>().launchpad_treasury {
+            Some(launchpad_treasury) => {
+                self.get_available_to_withdraw_payee_internal(launchpad_treasury)
+            }
+            None => 0,
+        }
+    }
+
+    fn get_available_to_withdraw_project_internal(&self) -> Balance {
+        let available_to_withdraw: Balance = self
+            .get_project_payees_internal()
+            .iter()
+            .map(|(payee, _)| {
+                self.get_accrued_sales_internal(*payee).saturating_sub(
+                    self.data::<Data>()
+                        .withdrawn_sales
+                        .get(payee)
+                        .unwrap_or_default(),
+                )
+            })
+            .sum();
+
+        available_to_withdraw.min(self.get_excess_balance_internal())
+    }
+
+    fn get_project_payees_internal(&self) -> Vec<(AccountId, BasisPoints)> {
+        let project_payees = self.data::<Data>().project_payees.get().unwrap_or_default();
+        if project_payees.is_empty() {
+            return match self.data::<Data>().project_treasury {
+                Some(project_treasury) => [(project_treasury, MAX_BASIS_POINTS)].to_vec(),
+                None => Vec::new(),
+            };
+        }
+        project_payees
+    }
+
+    fn get_accrued_sales_internal(&self, account_id: AccountId) -> Balance {
         let minting_status = self.get_current_minting_status();
         if minting_status != MintingStatus::End {
             return 0;
         }
 
-        let total_withdraw_share = self.get_total_withdraw_share_internal();
-        let launchpad_share =
-            (total_withdraw_share * self.data::<Data>().launchpad_fee).saturating_div(100);
+        let mut accrued_sales: Balance = 0;
+        if self.data::<Data>().launchpad_treasury == Some(account_id) {
+            let total_withdraw_share = self.get_total_withdraw_share_internal();
+            accrued_sales +=
+                (total_withdraw_share * self.data::<Data>().launchpad_fee).saturating_div(100);
+        }
+        if let Some((_, basis_points)) = self
+            .get_project_payees_internal()
+            .into_iter()
+            .find(|(payee, _)| *payee == account_id)
+        {
+            accrued_sales += (self.get_project_share_internal() * basis_points as Balance)
+                .saturating_div(MAX_BASIS_POINTS as Balance);
+        }
+        accrued_sales
+    }
+
+    fn get_available_to_withdraw_payee_internal(&self, account_id: AccountId) -> Balance {
+        self.get_accrued_sales_internal(account_id)
+            .saturating_sub(
+                self.data::<Data>()
+                    .withdrawn_sales
+                    .get(account_id)
+                    .unwrap_or_default(),
+            )
+            .min(self.get_excess_balance_internal())
+    }
 
-        launchpad_share - self.data::<Data>().withdrawn_sales_launchpad
+    fn get_withdrawn_sales_project_internal(&self) -> Balance {
+        self.get_project_payees_internal()
+            .iter()
+            .map(|(payee, _)| {
+                self.data::<Data>()
+                    .withdrawn_sales
+                    .get(payee)
+                    .unwrap_or_default()
+            })
+            .sum()
     }
 
-    fn get_available_to_withdraw_project_internal(&self) -> Balance {
+    fn withdraw_payee_internal(
+        &mut self,
+        account_id: AccountId,
+        destination: AccountId,
+        amount: Balance,
+    ) -> Result<(), PSP34Error> {
+        if amount > self.get_available_to_withdraw_payee_internal(account_id) {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::AmountExceedsAvailable.as_str(),
+            )));
+        }
+
+        let withdrawn_sales = self
+            .data::<Data>()
+            .withdrawn_sales
+            .get(account_id)
+            .unwrap_or_default();
+        self.data::<Data>()
+            .withdrawn_sales
+            .insert(account_id, &(withdrawn_sales + amount));
+
+        // a failed payout stays claimable by the payee, not by the destination
+        self.pay_to_or_credit(destination, account_id, amount);
+        Ok(())
+    }
+
+    fn get_address_internal(&self, address_kind: &AddressKind) -> Option<AccountId> {
+        match address_kind {
+            AddressKind::ProjectTreasury => self.data::<Data>().project_treasury,
+            AddressKind::LaunchpadTreasury => self.data::<Data>().launchpad_treasury,
+            AddressKind::RefundAddress => self.data::<Data>().refund_address,
+        }
+    }
+
+    fn check_address_available(
+        &self,
+        address_kind: &AddressKind,
+        new_address: AccountId,
+    ) -> Result<(), PSP34Error> {
+        // treasuries must stay distinct so their shares are not merged
+        let is_taken = match address_kind {
+            AddressKind::ProjectTreasury => {
+                Some(new_address) == self.data::<Data>().launchpad_treasury
+                    || self
+                        .get_project_payees_internal()
+                        .iter()
+                        .any(|(payee, _)| *payee == new_address)
+            }
+            AddressKind::LaunchpadTreasury => self
+                .get_project_payees_internal()
+                .iter()
+                .any(|(payee, _)| *payee == new_address),
+            AddressKind::RefundAddress => Some(new_address) == self.data::<Data>().refund_address,
+        };
+        if is_taken {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::InvalidInput.as_str(),
+            )));
+        }
+        Ok(())
+    }
+
+    fn set_address_internal(&mut self, address_kind: &AddressKind, new_address: AccountId) {
+        let old_address = self.get_address_internal(address_kind);
+        match address_kind {
+            AddressKind::ProjectTreasury => {
+                self.data::<Data>().project_treasury = Some(new_address);
+                let mut project_payees =
+                    self.data::<Data>().project_payees.get().unwrap_or_default();
+                for (payee, _) in project_payees.iter_mut() {
+                    if Some(*payee) == old_address {
+                        *payee = new_address;
+                    }
+                }
+                self.data::<Data>().project_payees.set(&project_payees);
+            }
+            AddressKind::LaunchpadTreasury => {
+                self.data::<Data>().launchpad_treasury = Some(new_address);
+            }
+            AddressKind::RefundAddress => {
+                self.data::<Data>().refund_address = Some(new_address);
+                return;
+            }
+        }
+
+        // the new treasury must not withdraw again what the old one already took
+        if let Some(old_address) = old_address {
+            let withdrawn_sales = self
+                .data::<Data>()
+                .withdrawn_sales
+                .get(old_address)
+                .unwrap_or_default();
+            self.data::<Data>().withdrawn_sales.remove(old_address);
+            self.data::<Data>()
+                .withdrawn_sales
+                .insert(new_address, &withdrawn_sales);
+        }
+    }
+
+    fn get_project_share_internal(&self) -> Balance {
+        self.get_vested_amount_internal(self.get_earned_project_share_internal())
+    }
+
+    fn get_vested_amount_internal(&self, amount: Balance) -> Balance {
+        let vesting_duration = self
+            .data::<Data>()
+            .vesting_duration
+            .get()
+            .unwrap_or_default();
+        if vesting_duration == 0 {
+            return amount;
+        }
+
+        let elapsed = Self::env()
+            .block_timestamp()
+            .saturating_sub(self.data::<Data>().public_sale_end_at);
+        if elapsed < self.data::<Data>().vesting_cliff.get().unwrap_or_default() {
+            return 0;
+        }
+        if elapsed >= vesting_duration {
+            return amount;
+        }
+        (amount * elapsed as Balance).saturating_div(vesting_duration as Balance)
+    }
+
+    fn get_earned_project_share_internal(&self) -> Balance {
         let minting_status = self.get_current_minting_status();
         if minting_status != MintingStatus::End {
             return 0;
         }
+        if self
+            .data::<Data>()
+            .project_defaulted
+            .get()
+            .unwrap_or_default()
+        {
+            return self
+                .data::<Data>()
+                .default_project_share
+                .get()
+                .unwrap_or_default();
+        }
 
         let total_withdraw_share = self.get_total_withdraw_share_internal();
-        let project_share =
+        let mut project_share =
             (total_withdraw_share * (100 - self.data::<Data>().launchpad_fee)).saturating_div(100);
 
-        project_share - self.data::<Data>().withdrawn_sales_project
+        // in milestone mode only the tranches approved so far are released
+        let milestones = self.data::<Data>().milestones.get().unwrap_or_default();
+        if !milestones.is_empty() {
+            let released_share: Percentage = milestones
+                .iter()
+                .filter(|(_, approved)| *approved)
+                .map(|(milestone_share, _)| milestone_share)
+                .sum();
+            project_share = (project_share * released_share).saturating_div(100);
+        }
+
+        project_share
     }
 
     fn get_total_withdraw_share_internal(&self) -> u128 {
@@ -634,7 +2108,12 @@ where
                 self.data::<Data>().total_sales - self.data::<Data>().total_refund;
         } else {
             for (i, refund_period) in self.data::<Data>().refund_periods.iter().enumerate() {
-                if current_timestamp < (self.data::<Data>().public_sale_end_at + refund_period) {
+                if current_timestamp
+                    < self
+                        .data::<Data>()
+                        .public_sale_end_at
+                        .saturating_add(*refund_period)
+                {
                     let non_refundable_percentage: Balance =
                         100 - *self.data::<Data>().refund_shares.get(i).unwrap_or(&100);
 
@@ -647,4 +2126,59 @@ where
         }
         return total_withdraw_share;
     }
+
+    default fn is_refundable_internal(&self, token_id: u64) -> bool {
+        if !self
+            .data::<Data>()
+            .minting_type_for_token
+            .contains(token_id)
+        {
+            return false;
+        }
+        if self
+            .data::<Data>()
+            .project_defaulted
+            .get()
+            .unwrap_or_default()
+        {
+            return true;
+        }
+
+        let current_timestamp = Self::env().block_timestamp();
+        self.get_refund_share_at_internal(current_timestamp)
+            .unwrap_or(0)
+            > 0
+    }
+
+    default fn set_collection_attribute_internal(
+        &mut self,
+        key: &str,
+        value: PreludeString,
+    ) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen(None)?;
+
+        let collection_id = self
+            .data::<psp34::Data<enumerable::Balances>>()
+            .collection_id();
+        let key = String::from(key);
+        if value.is_empty() {
+            self.data::<metadata::Data>()
+                .attributes
+                .remove(&(collection_id, key));
+        } else {
+            self.data::<metadata::Data>()
+                ._set_attribute(collection_id, key, value.into_bytes());
+        }
+        Ok(())
+    }
+
+    default fn get_collection_attribute_internal(&self, key: &str) -> Option<PreludeString> {
+        let collection_id = self
+            .data::<psp34::Data<enumerable::Balances>>()
+            .collection_id();
+        self.data::<metadata::Data>()
+            .attributes
+            .get(&(collection_id, String::from(key)))
+            .map(|value| PreludeString::from_utf8(value).unwrap())
+    }
 }
@@ -1,13 +1,45 @@
-use ink::prelude::vec::Vec;
-use ink::storage::Mapping;
-use openbrush::traits::{Balance, String};
+use ink::prelude::{string::String as PreludeString, vec::Vec};
+use ink::storage::{Lazy, Mapping};
+use openbrush::{
+    contracts::access_control::RoleType,
+    traits::{Balance, String},
+};
 pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
 
+/// Manages the prepresale and presale allowlists
+pub const WHITELIST_MANAGER: RoleType = ink::selector_id!("WHITELIST_MANAGER");
+/// Manages the base URI and token attributes
+pub const METADATA_ADMIN: RoleType = ink::selector_id!("METADATA_ADMIN");
+/// Manages the minting status and the max mint amount
+pub const PHASE_ADMIN: RoleType = ink::selector_id!("PHASE_ADMIN");
+/// Allowed to upgrade the contract code
+pub const UPGRADER: RoleType = ink::selector_id!("UPGRADER");
+/// Marketplaces reporting the price tokens are resold for, not granted to the owner
+pub const MARKETPLACE: RoleType = ink::selector_id!("MARKETPLACE");
+
 use ink::primitives::AccountId;
 pub type MilliSeconds = u64;
 pub type Percentage = u128;
 pub type TokenId = u64;
 pub type BlockTimestamp = u64;
+pub type BasisPoints = u16;
+/// Blake2x256 hash of an encoded `TimelockOperation`
+pub type OperationHash = [u8; 32];
+
+/// Basis points making up the whole project share
+pub const MAX_BASIS_POINTS: BasisPoints = 10_000;
+
+/// Version of the `Data` layout written by this code, bumped with every migration
+pub const STORAGE_VERSION: u32 = 2;
+
+/// Longest refund period (two years), keeping the refund deadlines far from overflowing
+pub const MAX_REFUND_PERIOD: MilliSeconds = 2 * 365 * 24 * 60 * 60 * 1000;
+
+/// Operations waiting for the timelock at once, bounding the pending list
+pub const MAX_PENDING_OPERATIONS: usize = 16;
+
+/// Precision of the pro-rata refund ratio applied once the project is declared in default
+pub const DEFAULT_REFUND_RATIO_PRECISION: u128 = 1_000_000_000;
 
 #[derive(PartialEq)]
 pub enum MintingStatus {
@@ -20,6 +52,157 @@ pub enum MintingStatus {
 
 pub type MintingStatusIndex = u8;
 
+#[derive(PartialEq)]
+pub enum RefundPolicy {
+    AnyHolder,
+    OriginalMinterOnly,
+    LastSalePriceCapped,
+}
+
+pub type RefundPolicyIndex = u8;
+
+#[derive(PartialEq)]
+pub enum AddressKind {
+    ProjectTreasury,
+    LaunchpadTreasury,
+    RefundAddress,
+}
+
+pub type AddressKindIndex = u8;
+
+#[derive(PartialEq)]
+pub enum CollectionAttribute {
+    Description,
+    Image,
+    Banner,
+    ExternalUrl,
+    Twitter,
+    Discord,
+}
+
+pub type CollectionAttributeIndex = u8;
+
+#[derive(PartialEq)]
+pub enum IdFormat {
+    Decimal,
+    Hex,
+}
+
+pub type IdFormatIndex = u8;
+
+/// Suffix appended to the token ID in token URIs until another one is set
+pub const DEFAULT_TOKEN_URI_SUFFIX: &str = ".json";
+
+/// Widest zero padding of the token ID in token URIs
+pub const MAX_TOKEN_ID_PADDING: u8 = 64;
+
+/// Name of the collection attribute holding the contract-level metadata URI
+pub const CONTRACT_URI_ATTRIBUTE: &str = "contractUri";
+
+impl AddressKind {
+    pub fn to_index(&self) -> u8 {
+        match self {
+            AddressKind::ProjectTreasury => return 0,
+            AddressKind::LaunchpadTreasury => return 1,
+            AddressKind::RefundAddress => return 2,
+        }
+    }
+
+    pub fn from(index: u8) -> Option<Self> {
+        if index == 0 {
+            return Some(AddressKind::ProjectTreasury);
+        } else if index == 1 {
+            return Some(AddressKind::LaunchpadTreasury);
+        } else if index == 2 {
+            return Some(AddressKind::RefundAddress);
+        } else {
+            return None;
+        }
+    }
+}
+
+impl CollectionAttribute {
+    pub fn to_index(&self) -> u8 {
+        match self {
+            CollectionAttribute::Description => return 0,
+            CollectionAttribute::Image => return 1,
+            CollectionAttribute::Banner => return 2,
+            CollectionAttribute::ExternalUrl => return 3,
+            CollectionAttribute::Twitter => return 4,
+            CollectionAttribute::Discord => return 5,
+        }
+    }
+
+    pub fn from(index: u8) -> Option<Self> {
+        if index == 0 {
+            return Some(CollectionAttribute::Description);
+        } else if index == 1 {
+            return Some(CollectionAttribute::Image);
+        } else if index == 2 {
+            return Some(CollectionAttribute::Banner);
+        } else if index == 3 {
+            return Some(CollectionAttribute::ExternalUrl);
+        } else if index == 4 {
+            return Some(CollectionAttribute::Twitter);
+        } else if index == 5 {
+            return Some(CollectionAttribute::Discord);
+        } else {
+            return None;
+        }
+    }
+
+    /// Name of the attribute set on the collection ID
+    pub fn as_key(&self) -> &'static str {
+        match self {
+            CollectionAttribute::Description => "description",
+            CollectionAttribute::Image => "image",
+            CollectionAttribute::Banner => "banner",
+            CollectionAttribute::ExternalUrl => "externalUrl",
+            CollectionAttribute::Twitter => "twitter",
+            CollectionAttribute::Discord => "discord",
+        }
+    }
+}
+
+impl IdFormat {
+    pub fn to_index(&self) -> u8 {
+        match self {
+            IdFormat::Decimal => return 0,
+            IdFormat::Hex => return 1,
+        }
+    }
+
+    pub fn from(index: u8) -> Option<Self> {
+        if index == 0 {
+            return Some(IdFormat::Decimal);
+        } else if index == 1 {
+            return Some(IdFormat::Hex);
+        } else {
+            return None;
+        }
+    }
+}
+
+impl RefundPolicy {
+    pub fn to_index(&self) -> u8 {
+        match self {
+            RefundPolicy::AnyHolder => return 0,
+            RefundPolicy::OriginalMinterOnly => return 1,
+            RefundPolicy::LastSalePriceCapped => return 2,
+        }
+    }
+
+    pub fn from(index: u8) -> Self {
+        if index == 1 {
+            return RefundPolicy::OriginalMinterOnly;
+        } else if index == 2 {
+            return RefundPolicy::LastSalePriceCapped;
+        } else {
+            return RefundPolicy::AnyHolder;
+        }
+    }
+}
+
 impl MintingStatus {
     pub fn to_index(&self) -> u8 {
         match self {
@@ -46,6 +229,9 @@ impl MintingStatus {
     }
 }
 
+/// The fields up to `attribute_names` are the layout of the first deployments and are packed in
+/// the contract root, so they must keep their order. Later fields are `Lazy` or `Mapping`, which
+/// take no room in the root and read as empty on contracts deployed before they existed.
 #[derive(Default, Debug)]
 #[openbrush::upgradeable_storage(STORAGE_KEY)]
 pub struct Data {
@@ -70,6 +256,8 @@ pub struct Data {
     pub minting_type_for_token: Mapping<TokenId, MintingStatusIndex>,
     pub total_sales: Balance,
     pub total_refund: Balance,
+    /// Withdrawn counters of the two treasuries before the per-payee ledger, moved to
+    /// `withdrawn_sales` by the v1 migration
     pub withdrawn_sales_project: Balance,
     pub withdrawn_sales_launchpad: Balance,
     pub launchpad_fee: Percentage,
@@ -77,6 +265,109 @@ pub struct Data {
     pub launchpad_treasury: Option<AccountId>,
     pub attribute_count: u32,
     pub attribute_names: Mapping<u32, Vec<u8>>,
+    pub refund_policy: Lazy<RefundPolicyIndex>,
+    pub original_minter: Mapping<TokenId, AccountId>,
+    pub last_sale_price: Mapping<TokenId, Balance>,
+    pub refundable_sales: Lazy<Balance>,
+    pub withdrawn_sales: Mapping<AccountId, Balance>,
+    pub pending_credits: Mapping<AccountId, Balance>,
+    pub total_pending_credits: Lazy<Balance>,
+    pub project_payees: Lazy<Vec<(AccountId, BasisPoints)>>,
+    pub pending_address_changes: Mapping<AddressKindIndex, AccountId>,
+    pub attribute_name_indexes: Mapping<Vec<u8>, u32>,
+    pub attribute_name_usage: Mapping<u32, u32>,
+    pub default_quorum: Lazy<Percentage>,
+    pub default_threshold: Lazy<Percentage>,
+    pub default_voting_period: Lazy<MilliSeconds>,
+    pub default_proposal_count: Lazy<u32>,
+    pub default_proposal: Lazy<Option<DefaultProposal>>,
+    pub default_votes: Mapping<(u32, TokenId), bool>,
+    pub project_defaulted: Lazy<bool>,
+    pub default_refund_ratio: Lazy<u128>,
+    pub milestones: Lazy<Vec<(Percentage, bool)>>,
+    pub milestone_arbiter: Lazy<Option<AccountId>>,
+    pub vesting_cliff: Lazy<MilliSeconds>,
+    pub vesting_duration: Lazy<MilliSeconds>,
+    pub default_royalty: Lazy<Option<(AccountId, BasisPoints)>>,
+    pub token_royalties: Mapping<TokenId, (AccountId, BasisPoints)>,
+    pub timelock_delay: Lazy<MilliSeconds>,
+    pub timelock_operation_count: Lazy<u32>,
+    pub timelock_operations: Mapping<OperationHash, ScheduledOperation>,
+    pub storage_version: Lazy<u32>,
+    pub metadata_frozen: Lazy<bool>,
+    pub frozen_tokens: Mapping<TokenId, bool>,
+    pub token_uris: Mapping<TokenId, Vec<u8>>,
+    pub on_chain_metadata: Lazy<bool>,
+    pub metadata_name_template: Lazy<Vec<u8>>,
+    pub metadata_description_template: Lazy<Vec<u8>>,
+    pub token_uri_suffix: Lazy<Vec<u8>>,
+    pub token_id_padding: Lazy<u8>,
+    pub token_id_format: Lazy<IdFormatIndex>,
+    pub transfer_lock: Lazy<bool>,
+    /// Last token ID handled by the running storage migration
+    pub migration_cursor: Lazy<TokenId>,
+    /// Project share of the milestones approved before the project defaulted
+    pub default_project_share: Lazy<Balance>,
+    /// Resale price reported by a marketplace for the transfer it is making
+    pub pending_sale_price: Mapping<TokenId, Balance>,
+    /// Hashes of the scheduled timelock operations, at most `MAX_PENDING_OPERATIONS`
+    pub timelock_pending: Lazy<Vec<OperationHash>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct DefaultProposal {
+    pub id: u32,
+    pub proposer: AccountId,
+    pub start_at: BlockTimestamp,
+    pub end_at: BlockTimestamp,
+    pub votes_for: Balance,
+    pub votes_against: Balance,
+}
+
+/// Admin action which has to wait for the timelock delay once one is set
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum TimelockOperation {
+    SetCode([u8; 32]),
+    SetRefundPeriods(Vec<MilliSeconds>),
+    SetMintingStatus(Option<u8>),
+    SetBaseUri(PreludeString),
+    SetTimelockDelay(MilliSeconds),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ScheduledOperation {
+    pub id: u32,
+    pub operation: TimelockOperation,
+    pub proposer: AccountId,
+    pub ready_at: BlockTimestamp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct FinancialSummary {
+    pub total_sales: Balance,
+    pub total_refund: Balance,
+    pub withdrawn_launchpad: Balance,
+    pub withdrawn_project: Balance,
+    pub available_to_withdraw_launchpad: Balance,
+    pub available_to_withdraw_project: Balance,
+    /// (payee, withdrawn, available to withdraw) of every project payee
+    pub project_payees: Vec<(AccountId, Balance, Balance)>,
+    pub refund_liability: Balance,
+    pub total_pending_credits: Balance,
+    pub contract_balance: Balance,
 }
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -91,6 +382,37 @@ pub enum Shiden34Error {
     UnableToMint,
     RefundFailed,
     Unauthorized,
+    RefundNotEligible,
+    InvalidInput,
+    NothingToClaim,
+    DefaultVotingNotOpen,
+    DefaultVotingNotEnded,
+    DefaultProposalActive,
+    AlreadyVoted,
+    ProjectDefaulted,
+    MilestoneAlreadyApproved,
+    MilestonesLocked,
+    PayeesLocked,
+    NoPendingAddressChange,
+    VestingLocked,
+    AmountExceedsAvailable,
+    OperationNotScheduled,
+    OperationNotReady,
+    RefundPeriodShortened,
+    RefundShareDecreased,
+    UpgradeFailed,
+    AlreadyMigrated,
+    MetadataFrozen,
+    TransferLocked,
+    TokenNotRefundable,
+    MigrationPending,
+    RefundPolicyLocked,
+    MintingNotEnded,
+    RefundLiabilityUncovered,
+    OperationAlreadyScheduled,
+    TooManyPendingOperations,
+    TransferLockLocked,
+    DefaultVoteConfigLocked,
 }
 
 impl Shiden34Error {
@@ -104,6 +426,37 @@ impl Shiden34Error {
             Shiden34Error::UnableToMint => String::from("UnableToMint"),
             Shiden34Error::RefundFailed => String::from("RefundFailed"),
             Shiden34Error::Unauthorized => String::from("Unauthorized"),
+            Shiden34Error::RefundNotEligible => String::from("RefundNotEligible"),
+            Shiden34Error::InvalidInput => String::from("InvalidInput"),
+            Shiden34Error::NothingToClaim => String::from("NothingToClaim"),
+            Shiden34Error::DefaultVotingNotOpen => String::from("DefaultVotingNotOpen"),
+            Shiden34Error::DefaultVotingNotEnded => String::from("DefaultVotingNotEnded"),
+            Shiden34Error::DefaultProposalActive => String::from("DefaultProposalActive"),
+            Shiden34Error::AlreadyVoted => String::from("AlreadyVoted"),
+            Shiden34Error::ProjectDefaulted => String::from("ProjectDefaulted"),
+            Shiden34Error::MilestoneAlreadyApproved => String::from("MilestoneAlreadyApproved"),
+            Shiden34Error::MilestonesLocked => String::from("MilestonesLocked"),
+            Shiden34Error::PayeesLocked => String::from("PayeesLocked"),
+            Shiden34Error::NoPendingAddressChange => String::from("NoPendingAddressChange"),
+            Shiden34Error::VestingLocked => String::from("VestingLocked"),
+            Shiden34Error::AmountExceedsAvailable => String::from("AmountExceedsAvailable"),
+            Shiden34Error::OperationNotScheduled => String::from("OperationNotScheduled"),
+            Shiden34Error::OperationNotReady => String::from("OperationNotReady"),
+            Shiden34Error::RefundPeriodShortened => String::from("RefundPeriodShortened"),
+            Shiden34Error::RefundShareDecreased => String::from("RefundShareDecreased"),
+            Shiden34Error::UpgradeFailed => String::from("UpgradeFailed"),
+            Shiden34Error::AlreadyMigrated => String::from("AlreadyMigrated"),
+            Shiden34Error::MetadataFrozen => String::from("MetadataFrozen"),
+            Shiden34Error::TransferLocked => String::from("TransferLocked"),
+            Shiden34Error::TokenNotRefundable => String::from("TokenNotRefundable"),
+            Shiden34Error::MigrationPending => String::from("MigrationPending"),
+            Shiden34Error::RefundPolicyLocked => String::from("RefundPolicyLocked"),
+            Shiden34Error::MintingNotEnded => String::from("MintingNotEnded"),
+            Shiden34Error::RefundLiabilityUncovered => String::from("RefundLiabilityUncovered"),
+            Shiden34Error::OperationAlreadyScheduled => String::from("OperationAlreadyScheduled"),
+            Shiden34Error::TooManyPendingOperations => String::from("TooManyPendingOperations"),
+            Shiden34Error::TransferLockLocked => String::from("TransferLockLocked"),
+            Shiden34Error::DefaultVoteConfigLocked => String::from("DefaultVoteConfigLocked"),
         }
     }
 }
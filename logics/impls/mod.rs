@@ -1,2 +1,4 @@
 pub mod launchpad;
-pub mod psp34_traits;
\ No newline at end of file
+pub mod psp34_traits;
+pub mod royalty;
+pub mod timelock;
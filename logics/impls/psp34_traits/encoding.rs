@@ -0,0 +1,58 @@
+use ink::prelude::{format, string::String as PreludeString, vec::Vec};
+
+use crate::impls::launchpad::types::IdFormat;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode bytes as standard padded base64
+pub fn base64_encode(input: &[u8]) -> PreludeString {
+    let mut output = Vec::with_capacity((input.len() + 2) / 3 * 4);
+    for chunk in input.chunks(3) {
+        let triple = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+
+        output.push(BASE64_ALPHABET[(triple >> 18) as usize & 63]);
+        output.push(BASE64_ALPHABET[(triple >> 12) as usize & 63]);
+        if chunk.len() > 1 {
+            output.push(BASE64_ALPHABET[(triple >> 6) as usize & 63]);
+        } else {
+            output.push(b'=');
+        }
+        if chunk.len() > 2 {
+            output.push(BASE64_ALPHABET[triple as usize & 63]);
+        } else {
+            output.push(b'=');
+        }
+    }
+    // the alphabet is plain ascii
+    PreludeString::from_utf8(output).unwrap()
+}
+
+/// Format a token ID as decimal or lowercase hex, left-padded with zeros to `padding` digits
+pub fn format_token_id(token_id: u64, id_format: &IdFormat, padding: usize) -> PreludeString {
+    match id_format {
+        IdFormat::Decimal => format!("{:0width$}", token_id, width = padding),
+        IdFormat::Hex => format!("{:0width$x}", token_id, width = padding),
+    }
+}
+
+/// Escape a value so it can be placed between double quotes in a JSON document
+pub fn escape_json(input: &str) -> PreludeString {
+    let mut output = PreludeString::with_capacity(input.len());
+    for character in input.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                output.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => output.push(character),
+        }
+    }
+    output
+}
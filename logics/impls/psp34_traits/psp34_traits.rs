@@ -1,11 +1,21 @@
-use ink::prelude::string::{String as PreludeString, ToString};
+use ink::prelude::{
+    format,
+    string::{String as PreludeString, ToString},
+};
+
+use crate::impls::psp34_traits::encoding::{base64_encode, escape_json, format_token_id};
 
-use crate::impls::launchpad::types::Data;
+use crate::impls::launchpad::types::{
+    Data, IdFormat, IdFormatIndex, Shiden34Error, TimelockOperation, TokenId,
+    DEFAULT_TOKEN_URI_SUFFIX, MAX_TOKEN_ID_PADDING, METADATA_ADMIN, STORAGE_VERSION,
+};
 pub use crate::traits::psp34_traits::Psp34Traits;
+use crate::traits::timelock::Timelock;
 
 use ink::prelude::vec::Vec;
 use openbrush::{
     contracts::{
+        access_control::{self, only_role},
         ownable::*,
         psp34::extensions::{enumerable::*, metadata::*},
     },
@@ -15,11 +25,17 @@ use openbrush::{
 
 pub trait Internal {
     fn token_exists(&self, id: Id) -> Result<(), PSP34Error>;
+
+    /// Check the metadata of the collection, or of the token when given, can still change
+    fn check_metadata_not_frozen(&self, token_id: Option<&Id>) -> Result<(), PSP34Error>;
+
+    /// Check no storage migration is running, it rebuilds the attribute name registry
+    fn check_migrated(&self) -> Result<(), PSP34Error>;
 }
 
 impl<T> Internal for T
 where
-    T: Storage<psp34::Data<enumerable::Balances>>,
+    T: Storage<psp34::Data<enumerable::Balances>> + Storage<Data>,
 {
     /// Check if token is minted
     default fn token_exists(&self, id: Id) -> Result<(), PSP34Error> {
@@ -28,31 +44,130 @@ where
             .ok_or(PSP34Error::TokenNotExists)?;
         Ok(())
     }
+
+    default fn check_metadata_not_frozen(&self, token_id: Option<&Id>) -> Result<(), PSP34Error> {
+        let is_token_frozen = match token_id {
+            Some(Id::U64(token_id)) => self
+                .data::<Data>()
+                .frozen_tokens
+                .get(token_id)
+                .unwrap_or(false),
+            _ => false,
+        };
+        if self
+            .data::<Data>()
+            .metadata_frozen
+            .get()
+            .unwrap_or_default()
+            || is_token_frozen
+        {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::MetadataFrozen.as_str(),
+            )));
+        }
+        Ok(())
+    }
+
+    default fn check_migrated(&self) -> Result<(), PSP34Error> {
+        if self
+            .data::<Data>()
+            .storage_version
+            .get()
+            .unwrap_or_default()
+            < STORAGE_VERSION
+        {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::MigrationPending.as_str(),
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl<T> Psp34Traits for T
 where
     T: Storage<psp34::Data<enumerable::Balances>>
         + Storage<ownable::Data>
+        + Storage<access_control::Data>
         + Storage<metadata::Data>
         + psp34::extensions::metadata::PSP34Metadata
-        + Storage<Data>,
+        + Storage<Data>
+        + Timelock,
 {
     /// Get URI from token ID
     default fn token_uri(&self, token_id: u64) -> PreludeString {
+        if let Some(token_uri) = self.data::<Data>().token_uris.get(token_id) {
+            return PreludeString::from_utf8(token_uri).unwrap();
+        }
+        if self.is_on_chain_metadata() {
+            let metadata = render_on_chain_metadata(self, token_id);
+            return PreludeString::from("data:application/json;base64,")
+                + &base64_encode(metadata.as_bytes());
+        }
+
         let value = self.get_attribute(
             self.data::<psp34::Data<enumerable::Balances>>()
                 .collection_id(),
             String::from("baseUri"),
         );
-        let mut token_uri = PreludeString::from_utf8(value.unwrap()).unwrap();
-        token_uri = token_uri + &token_id.to_string() + &PreludeString::from(".json");
+        let (suffix, padding, id_format_index) = self.get_uri_format();
+        let id_format = IdFormat::from(id_format_index).unwrap_or(IdFormat::Decimal);
+        let mut token_uri = PreludeString::from_utf8(value.unwrap_or_default()).unwrap();
+        token_uri = token_uri + &format_token_id(token_id, &id_format, padding as usize) + &suffix;
         token_uri
     }
 
+    /// Get URI from token ID, failing for tokens which are not minted
+    default fn get_token_uri(&self, token_id: u64) -> Result<PreludeString, PSP34Error> {
+        self.token_exists(Id::U64(token_id))?;
+        Ok(self.token_uri(token_id))
+    }
+
+    /// Set how the token ID is appended to the baseUri
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn set_uri_format(
+        &mut self,
+        suffix: PreludeString,
+        padding: u8,
+        id_format_index: IdFormatIndex,
+    ) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen(None)?;
+        if padding > MAX_TOKEN_ID_PADDING || IdFormat::from(id_format_index).is_none() {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::InvalidInput.as_str(),
+            )));
+        }
+        self.data::<Data>()
+            .token_uri_suffix
+            .set(&suffix.into_bytes());
+        self.data::<Data>().token_id_padding.set(&padding);
+        self.data::<Data>().token_id_format.set(&id_format_index);
+        Ok(())
+    }
+
+    default fn get_uri_format(&self) -> (PreludeString, u8, IdFormatIndex) {
+        let suffix = match self.data::<Data>().token_uri_suffix.get() {
+            Some(suffix) => PreludeString::from_utf8(suffix).unwrap(),
+            None => PreludeString::from(DEFAULT_TOKEN_URI_SUFFIX),
+        };
+        (
+            suffix,
+            self.data::<Data>()
+                .token_id_padding
+                .get()
+                .unwrap_or_default(),
+            self.data::<Data>()
+                .token_id_format
+                .get()
+                .unwrap_or_default(),
+        )
+    }
+
     /// Set new value for the baseUri
-    #[modifiers(only_owner)]
+    #[modifiers(only_role(METADATA_ADMIN))]
     default fn set_base_uri(&mut self, uri: PreludeString) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen(None)?;
+        self._check_timelock(TimelockOperation::SetBaseUri(uri.clone()))?;
         let id = self
             .data::<psp34::Data<enumerable::Balances>>()
             .collection_id();
@@ -61,23 +176,162 @@ where
         Ok(())
     }
 
-    /// Only Owner can set multiple attributes to a token
-    #[modifiers(only_owner)]
+    /// Set a URI for the token which takes precedence over the baseUri
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn set_token_uri(
+        &mut self,
+        token_id: TokenId,
+        uri: PreludeString,
+    ) -> Result<(), PSP34Error> {
+        set_token_uri_internal(self, token_id, Some(uri))
+    }
+
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn set_token_uri_batch(
+        &mut self,
+        token_uris: Vec<(TokenId, PreludeString)>,
+    ) -> Result<(), PSP34Error> {
+        for (token_id, uri) in token_uris {
+            set_token_uri_internal(self, token_id, Some(uri))?;
+        }
+        Ok(())
+    }
+
+    /// Serve the metadata as a base64 JSON document built from the token attributes instead of the baseUri
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn set_on_chain_metadata(&mut self, enabled: bool) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen(None)?;
+        self.data::<Data>().on_chain_metadata.set(&enabled);
+        Ok(())
+    }
+
+    default fn is_on_chain_metadata(&self) -> bool {
+        self.data::<Data>()
+            .on_chain_metadata
+            .get()
+            .unwrap_or_default()
+    }
+
+    /// Set the name and description of tokens without their own, `{id}` is replaced by the token ID
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn set_metadata_template(
+        &mut self,
+        name: PreludeString,
+        description: PreludeString,
+    ) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen(None)?;
+        self.data::<Data>()
+            .metadata_name_template
+            .set(&name.into_bytes());
+        self.data::<Data>()
+            .metadata_description_template
+            .set(&description.into_bytes());
+        Ok(())
+    }
+
+    default fn get_metadata_template(&self) -> (PreludeString, PreludeString) {
+        (
+            PreludeString::from_utf8(
+                self.data::<Data>()
+                    .metadata_name_template
+                    .get()
+                    .unwrap_or_default(),
+            )
+            .unwrap(),
+            PreludeString::from_utf8(
+                self.data::<Data>()
+                    .metadata_description_template
+                    .get()
+                    .unwrap_or_default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    /// Remove the URI override, the token falls back to the baseUri
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn remove_token_uri(&mut self, token_id: TokenId) -> Result<(), PSP34Error> {
+        set_token_uri_internal(self, token_id, None)
+    }
+
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn remove_token_uri_batch(
+        &mut self,
+        token_ids: Vec<TokenId>,
+    ) -> Result<(), PSP34Error> {
+        for token_id in token_ids {
+            set_token_uri_internal(self, token_id, None)?;
+        }
+        Ok(())
+    }
+
+    /// Only metadata admins can set multiple attributes to a token
+    #[modifiers(only_role(METADATA_ADMIN))]
     default fn set_multiple_attributes(
         &mut self,
         token_id: Id,
         metadata: Vec<(PreludeString, PreludeString)>,
+    ) -> Result<(), PSP34Error> {
+        // only tokens the contract can mint, which the migrations walk through
+        let max_supply = self.data::<Data>().max_supply;
+        match token_id {
+            Id::U64(id) if id > 0 && id <= max_supply => {}
+            _ => return Err(PSP34Error::Custom("InvalidInput".as_bytes().to_vec())),
+        }
+        set_attributes_internal(self, token_id, metadata)
+    }
+
+    /// Set attributes of many tokens, an invalid item is reported back without aborting the batch
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn set_attributes_batch(
+        &mut self,
+        items: Vec<(Id, Vec<(PreludeString, PreludeString)>)>,
+    ) -> Result<Vec<(Id, PSP34Error)>, PSP34Error> {
+        let max_supply = self.data::<Data>().max_supply;
+        let mut failures = Vec::<(Id, PSP34Error)>::new();
+        let mut updated: u32 = 0;
+        for (token_id, metadata) in items {
+            let result = match token_id {
+                Id::U64(id) if id > 0 && id <= max_supply => {
+                    set_attributes_internal(self, token_id.clone(), metadata)
+                }
+                _ => Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::InvalidInput.as_str(),
+                ))),
+            };
+            match result {
+                Ok(()) => updated += 1,
+                Err(error) => failures.push((token_id, error)),
+            }
+        }
+
+        self._emit_attributes_batch_set_event(updated, failures.len() as u32);
+        Ok(failures)
+    }
+
+    /// Remove attributes from a token, names no token uses anymore are dropped from the registry
+    #[modifiers(only_role(METADATA_ADMIN))]
+    default fn remove_attributes(
+        &mut self,
+        token_id: Id,
+        attributes: Vec<PreludeString>,
     ) -> Result<(), PSP34Error> {
         if token_id == Id::U64(0) {
             return Err(PSP34Error::Custom("InvalidInput".as_bytes().to_vec()));
         }
-        for (attribute, value) in &metadata {
-            add_attribute_name(self, &attribute.clone().into_bytes());
-            self._set_attribute(
-                token_id.clone(),
-                attribute.clone().into_bytes(),
-                value.clone().into_bytes(),
-            );
+        self.check_metadata_not_frozen(Some(&token_id))?;
+        self.check_migrated()?;
+        for attribute in attributes {
+            let attribute_name = attribute.into_bytes();
+            if self
+                .get_attribute(token_id.clone(), attribute_name.clone())
+                .is_some()
+            {
+                self.data::<metadata::Data>()
+                    .attributes
+                    .remove(&(token_id.clone(), attribute_name.clone()));
+                remove_attribute_name(self, &attribute_name);
+            }
         }
         Ok(())
     }
@@ -101,6 +355,71 @@ where
         ret
     }
 
+    /// Permanently lock the base URI and the attributes of every token
+    #[modifiers(only_owner)]
+    default fn freeze_metadata(&mut self) -> Result<(), PSP34Error> {
+        self.check_metadata_not_frozen(None)?;
+        self.data::<Data>().metadata_frozen.set(&true);
+        self._emit_metadata_frozen_event(None);
+        Ok(())
+    }
+
+    /// Permanently lock the attributes of a single token
+    #[modifiers(only_owner)]
+    default fn freeze_token_metadata(&mut self, token_id: TokenId) -> Result<(), PSP34Error> {
+        if token_id == 0 {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::InvalidInput.as_str(),
+            )));
+        }
+        self.check_metadata_not_frozen(Some(&Id::U64(token_id)))?;
+        self.data::<Data>().frozen_tokens.insert(token_id, &true);
+        self._emit_metadata_frozen_event(Some(token_id));
+        Ok(())
+    }
+
+    default fn is_metadata_frozen(&self, token_id: TokenId) -> bool {
+        self.check_metadata_not_frozen(Some(&Id::U64(token_id)))
+            .is_err()
+    }
+
+    default fn _emit_metadata_frozen_event(&self, _token_id: Option<TokenId>) {}
+
+    default fn _emit_attributes_batch_set_event(&self, _updated: u32, _failed: u32) {}
+
+    /// Get every attribute set on the token
+    default fn get_all_attributes(&self, token_id: Id) -> Vec<(PreludeString, PreludeString)> {
+        self.get_attributes_page(token_id, 0, self.data::<Data>().attribute_count)
+    }
+
+    /// Get the attributes set on the token among `limit` attribute names starting at index `from`
+    default fn get_attributes_page(
+        &self,
+        token_id: Id,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(PreludeString, PreludeString)> {
+        let mut ret = Vec::<(PreludeString, PreludeString)>::new();
+        // attribute names are stored at indexes 1..=attribute_count
+        let first = from.saturating_add(1);
+        let to = first
+            .saturating_add(limit)
+            .min(self.data::<Data>().attribute_count.saturating_add(1));
+        for index in first..to {
+            let attribute_name = match self.data::<Data>().attribute_names.get(&index) {
+                Some(attribute_name) => attribute_name,
+                None => continue,
+            };
+            if let Some(value) = self.get_attribute(token_id.clone(), attribute_name.clone()) {
+                ret.push((
+                    PreludeString::from_utf8(attribute_name).unwrap(),
+                    PreludeString::from_utf8(value).unwrap(),
+                ));
+            }
+        }
+        ret
+    }
+
     /// Get Attribute Count
     default fn get_attribute_count(&self) -> u32 {
         self.data::<Data>().attribute_count
@@ -116,23 +435,170 @@ where
     }
 }
 
+/// Build the JSON metadata of the token from its name, description, image and other attributes
+fn render_on_chain_metadata<T>(instance: &T, token_id: TokenId) -> PreludeString
+where
+    T: Storage<psp34::Data<enumerable::Balances>> + Storage<Data> + PSP34Metadata,
+{
+    let get_value = |id: Id, attribute: &[u8]| {
+        instance
+            .get_attribute(id, attribute.to_vec())
+            .map(|value| PreludeString::from_utf8(value).unwrap())
+    };
+    let fill_template = |template: &Vec<u8>| {
+        PreludeString::from_utf8(template.clone())
+            .unwrap()
+            .replace("{id}", &token_id.to_string())
+    };
+    let id = Id::U64(token_id);
+    let reserved_attributes: [&[u8]; 3] = [b"name", b"description", b"image"];
+
+    let name = get_value(id.clone(), b"name").unwrap_or_else(|| {
+        let name_template = instance
+            .data::<Data>()
+            .metadata_name_template
+            .get()
+            .unwrap_or_default();
+        if name_template.is_empty() {
+            let collection_id = instance
+                .data::<psp34::Data<enumerable::Balances>>()
+                .collection_id();
+            get_value(collection_id, b"name").unwrap_or_default() + " #" + &token_id.to_string()
+        } else {
+            fill_template(&name_template)
+        }
+    });
+    let description = get_value(id.clone(), b"description").unwrap_or_else(|| {
+        fill_template(
+            &instance
+                .data::<Data>()
+                .metadata_description_template
+                .get()
+                .unwrap_or_default(),
+        )
+    });
+    let image = get_value(id.clone(), b"image").unwrap_or_default();
+
+    let mut attributes = Vec::<PreludeString>::new();
+    for index in 1..=instance.data::<Data>().attribute_count {
+        let attribute_name = match instance.data::<Data>().attribute_names.get(&index) {
+            Some(attribute_name) => attribute_name,
+            None => continue,
+        };
+        if reserved_attributes.contains(&attribute_name.as_slice()) {
+            continue;
+        }
+        if let Some(value) = get_value(id.clone(), &attribute_name) {
+            attributes.push(format!(
+                "{{\"trait_type\":\"{}\",\"value\":\"{}\"}}",
+                escape_json(&PreludeString::from_utf8(attribute_name).unwrap()),
+                escape_json(&value)
+            ));
+        }
+    }
+
+    format!(
+        "{{\"name\":\"{}\",\"description\":\"{}\",\"image\":\"{}\",\"attributes\":[{}]}}",
+        escape_json(&name),
+        escape_json(&description),
+        escape_json(&image),
+        attributes.join(",")
+    )
+}
+
+fn set_token_uri_internal<T: Storage<Data> + Internal>(
+    instance: &mut T,
+    token_id: TokenId,
+    uri: Option<PreludeString>,
+) -> Result<(), PSP34Error> {
+    if token_id == 0 || uri.as_ref().map_or(false, |uri| uri.is_empty()) {
+        return Err(PSP34Error::Custom(String::from(
+            Shiden34Error::InvalidInput.as_str(),
+        )));
+    }
+    instance.check_metadata_not_frozen(Some(&Id::U64(token_id)))?;
+
+    match uri {
+        Some(uri) => {
+            instance
+                .data::<Data>()
+                .token_uris
+                .insert(token_id, &uri.into_bytes());
+        }
+        None => instance.data::<Data>().token_uris.remove(token_id),
+    }
+    Ok(())
+}
+
+fn set_attributes_internal<T>(
+    instance: &mut T,
+    token_id: Id,
+    metadata: Vec<(PreludeString, PreludeString)>,
+) -> Result<(), PSP34Error>
+where
+    T: Storage<Data> + Storage<metadata::Data> + PSP34Metadata + Internal,
+{
+    instance.check_metadata_not_frozen(Some(&token_id))?;
+    instance.check_migrated()?;
+    for (attribute, value) in metadata {
+        let attribute_name = attribute.into_bytes();
+        if instance
+            .get_attribute(token_id.clone(), attribute_name.clone())
+            .is_none()
+        {
+            add_attribute_name(instance, &attribute_name);
+        }
+        instance.data::<metadata::Data>()._set_attribute(
+            token_id.clone(),
+            attribute_name,
+            value.into_bytes(),
+        );
+    }
+    Ok(())
+}
+
+/// Count one more token using the attribute name, registering the name on first use
 fn add_attribute_name<T: Storage<Data>>(instance: &mut T, attribute_input: &Vec<u8>) {
-    let mut exist: bool = false;
-    for index in 0..instance.data::<Data>().attribute_count {
-        let attribute_name = instance.data::<Data>().attribute_names.get(&(index + 1));
-        if attribute_name.is_some() && attribute_name.unwrap() == *attribute_input {
-            exist = true;
-            break;
+    let data = instance.data::<Data>();
+    let index = match data.attribute_name_indexes.get(attribute_input) {
+        Some(index) => index,
+        None => {
+            data.attribute_count = data.attribute_count.checked_add(1).unwrap();
+            data.attribute_names
+                .insert(&data.attribute_count, attribute_input);
+            data.attribute_name_indexes
+                .insert(attribute_input, &data.attribute_count);
+            data.attribute_count
         }
+    };
+    let usage = data.attribute_name_usage.get(&index).unwrap_or(0);
+    data.attribute_name_usage.insert(&index, &(usage + 1));
+}
+
+/// Count one less token using the attribute name, dropping the name once unused.
+/// The last name takes the freed index so names stay indexed 1..=attribute_count
+fn remove_attribute_name<T: Storage<Data>>(instance: &mut T, attribute_input: &Vec<u8>) {
+    let data = instance.data::<Data>();
+    let index = match data.attribute_name_indexes.get(attribute_input) {
+        Some(index) => index,
+        None => return,
+    };
+    let usage = data.attribute_name_usage.get(&index).unwrap_or(0);
+    if usage > 1 {
+        data.attribute_name_usage.insert(&index, &(usage - 1));
+        return;
     }
-    if !exist {
-        instance.data::<Data>().attribute_count = instance
-            .data::<Data>()
-            .attribute_count
-            .checked_add(1)
-            .unwrap();
-        let data = &mut instance.data::<Data>();
-        data.attribute_names
-            .insert(&data.attribute_count, attribute_input);
+
+    let last_index = data.attribute_count;
+    if index != last_index {
+        let last_name = data.attribute_names.get(&last_index).unwrap_or_default();
+        let last_usage = data.attribute_name_usage.get(&last_index).unwrap_or(0);
+        data.attribute_names.insert(&index, &last_name);
+        data.attribute_name_indexes.insert(&last_name, &index);
+        data.attribute_name_usage.insert(&index, &last_usage);
     }
+    data.attribute_names.remove(&last_index);
+    data.attribute_name_usage.remove(&last_index);
+    data.attribute_name_indexes.remove(attribute_input);
+    data.attribute_count = last_index - 1;
 }
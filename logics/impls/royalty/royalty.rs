@@ -0,0 +1,93 @@
+use crate::impls::launchpad::types::{BasisPoints, Data, Shiden34Error, MAX_BASIS_POINTS};
+pub use crate::traits::royalty::Royalty;
+
+use openbrush::{
+    contracts::{ownable::*, psp34::PSP34Error},
+    modifiers,
+    traits::{AccountId, Balance, Storage, String},
+};
+
+pub trait Internal {
+    /// Check the royalty does not exceed the sale price
+    fn check_royalty(&self, royalty: &Option<(AccountId, BasisPoints)>) -> Result<(), PSP34Error>;
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn check_royalty(
+        &self,
+        royalty: &Option<(AccountId, BasisPoints)>,
+    ) -> Result<(), PSP34Error> {
+        if let Some((_, basis_points)) = royalty {
+            if *basis_points > MAX_BASIS_POINTS {
+                return Err(PSP34Error::Custom(String::from(
+                    Shiden34Error::InvalidInput.as_str(),
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T> Royalty for T
+where
+    T: Storage<Data> + Storage<ownable::Data>,
+{
+    default fn royalty_info(
+        &self,
+        token_id: u64,
+        sale_price: Balance,
+    ) -> Option<(AccountId, Balance)> {
+        let (receiver, basis_points) = self
+            .data::<Data>()
+            .token_royalties
+            .get(token_id)
+            .or(self.get_default_royalty())?;
+        // huge prices are divided first so the amount can't overflow
+        let royalty_amount = match sale_price.checked_mul(basis_points as Balance) {
+            Some(amount) => amount.saturating_div(MAX_BASIS_POINTS as Balance),
+            None => {
+                sale_price.saturating_div(MAX_BASIS_POINTS as Balance) * basis_points as Balance
+            }
+        };
+        Some((receiver, royalty_amount))
+    }
+
+    #[modifiers(only_owner)]
+    default fn set_default_royalty(
+        &mut self,
+        royalty: Option<(AccountId, BasisPoints)>,
+    ) -> Result<(), PSP34Error> {
+        self.check_royalty(&royalty)?;
+        self.data::<Data>().default_royalty.set(&royalty);
+        Ok(())
+    }
+
+    default fn get_default_royalty(&self) -> Option<(AccountId, BasisPoints)> {
+        self.data::<Data>().default_royalty.get().flatten()
+    }
+
+    #[modifiers(only_owner)]
+    default fn set_token_royalty(
+        &mut self,
+        token_id: u64,
+        royalty: Option<(AccountId, BasisPoints)>,
+    ) -> Result<(), PSP34Error> {
+        self.check_royalty(&royalty)?;
+        match royalty {
+            Some(royalty) => {
+                self.data::<Data>()
+                    .token_royalties
+                    .insert(token_id, &royalty);
+            }
+            None => self.data::<Data>().token_royalties.remove(token_id),
+        }
+        Ok(())
+    }
+
+    default fn get_token_royalty(&self, token_id: u64) -> Option<(AccountId, BasisPoints)> {
+        self.data::<Data>().token_royalties.get(token_id)
+    }
+}
@@ -0,0 +1,227 @@
+use crate::impls::launchpad::types::{
+    BlockTimestamp, Data, MilliSeconds, OperationHash, ScheduledOperation, Shiden34Error,
+    TimelockOperation, MAX_PENDING_OPERATIONS, METADATA_ADMIN, PHASE_ADMIN, UPGRADER,
+};
+pub use crate::traits::timelock::Timelock;
+
+use ink::{
+    env::hash::{Blake2x256, HashOutput},
+    prelude::vec::Vec,
+};
+use openbrush::{
+    contracts::{access_control::AccessControl, ownable::*, psp34::PSP34Error},
+    modifiers,
+    traits::{AccountId, Storage, String},
+};
+
+pub trait Internal {
+    /// Check the account may schedule or cancel the operation
+    fn check_operation_permission(
+        &self,
+        operation: &TimelockOperation,
+        account_id: AccountId,
+    ) -> Result<(), PSP34Error>;
+
+    /// Key the operation is stored under while pending
+    fn operation_hash(&self, operation: &TimelockOperation) -> OperationHash;
+
+    /// Drop an executed or cancelled operation from storage and the pending list
+    fn remove_operation(&mut self, operation_hash: OperationHash);
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + AccessControl,
+{
+    default fn check_operation_permission(
+        &self,
+        operation: &TimelockOperation,
+        account_id: AccountId,
+    ) -> Result<(), PSP34Error> {
+        let is_allowed = match operation {
+            TimelockOperation::SetCode(_) => self.has_role(UPGRADER, account_id),
+            TimelockOperation::SetMintingStatus(_) => self.has_role(PHASE_ADMIN, account_id),
+            TimelockOperation::SetBaseUri(_) => self.has_role(METADATA_ADMIN, account_id),
+            TimelockOperation::SetRefundPeriods(_) | TimelockOperation::SetTimelockDelay(_) => {
+                account_id == self.data::<ownable::Data>().owner
+            }
+        };
+        if !is_allowed {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::Unauthorized.as_str(),
+            )));
+        }
+        Ok(())
+    }
+
+    default fn operation_hash(&self, operation: &TimelockOperation) -> OperationHash {
+        let mut output = <Blake2x256 as HashOutput>::Type::default();
+        ink::env::hash_encoded::<Blake2x256, _>(operation, &mut output);
+        output
+    }
+
+    default fn remove_operation(&mut self, operation_hash: OperationHash) {
+        self.data::<Data>()
+            .timelock_operations
+            .remove(operation_hash);
+        let mut pending = self
+            .data::<Data>()
+            .timelock_pending
+            .get()
+            .unwrap_or_default();
+        if let Some(index) = pending.iter().position(|hash| *hash == operation_hash) {
+            pending.swap_remove(index);
+            self.data::<Data>().timelock_pending.set(&pending);
+        }
+    }
+}
+
+impl<T> Timelock for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + AccessControl,
+{
+    default fn schedule_operation(
+        &mut self,
+        operation: TimelockOperation,
+    ) -> Result<u32, PSP34Error> {
+        let caller_id = Self::env().caller();
+        self.check_operation_permission(&operation, caller_id)?;
+
+        let operation_hash = self.operation_hash(&operation);
+        if self
+            .data::<Data>()
+            .timelock_operations
+            .contains(operation_hash)
+        {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::OperationAlreadyScheduled.as_str(),
+            )));
+        }
+        let mut pending = self
+            .data::<Data>()
+            .timelock_pending
+            .get()
+            .unwrap_or_default();
+        if pending.len() >= MAX_PENDING_OPERATIONS {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::TooManyPendingOperations.as_str(),
+            )));
+        }
+
+        let operation_id = self
+            .data::<Data>()
+            .timelock_operation_count
+            .get()
+            .unwrap_or_default();
+        let ready_at = Self::env()
+            .block_timestamp()
+            .saturating_add(self.get_timelock_delay());
+        self.data::<Data>()
+            .timelock_operation_count
+            .set(&(operation_id + 1));
+        self.data::<Data>().timelock_operations.insert(
+            operation_hash,
+            &ScheduledOperation {
+                id: operation_id,
+                operation: operation.clone(),
+                proposer: caller_id,
+                ready_at,
+            },
+        );
+        pending.push(operation_hash);
+        self.data::<Data>().timelock_pending.set(&pending);
+
+        self._emit_operation_scheduled_event(operation_id, operation, caller_id, ready_at);
+        Ok(operation_id)
+    }
+
+    default fn cancel_operation(&mut self, operation_id: u32) -> Result<(), PSP34Error> {
+        let caller_id = Self::env().caller();
+        let (operation_hash, scheduled) = self
+            .data::<Data>()
+            .timelock_pending
+            .get()
+            .unwrap_or_default()
+            .into_iter()
+            .find_map(|operation_hash| {
+                self.data::<Data>()
+                    .timelock_operations
+                    .get(operation_hash)
+                    .filter(|scheduled| scheduled.id == operation_id)
+                    .map(|scheduled| (operation_hash, scheduled))
+            })
+            .ok_or(PSP34Error::Custom(String::from(
+                Shiden34Error::OperationNotScheduled.as_str(),
+            )))?;
+
+        if caller_id != scheduled.proposer && caller_id != self.data::<ownable::Data>().owner {
+            return Err(PSP34Error::Custom(String::from(
+                Shiden34Error::Unauthorized.as_str(),
+            )));
+        }
+
+        self.remove_operation(operation_hash);
+        self._emit_operation_cancelled_event(operation_id);
+        Ok(())
+    }
+
+    default fn get_pending_operations(&self) -> Vec<ScheduledOperation> {
+        self.data::<Data>()
+            .timelock_pending
+            .get()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|operation_hash| {
+                self.data::<Data>().timelock_operations.get(operation_hash)
+            })
+            .collect()
+    }
+
+    #[modifiers(only_owner)]
+    default fn set_timelock_delay(&mut self, delay: MilliSeconds) -> Result<(), PSP34Error> {
+        // lengthening the delay only protects holders further
+        if delay < self.get_timelock_delay() {
+            self._check_timelock(TimelockOperation::SetTimelockDelay(delay))?;
+        }
+        self.data::<Data>().timelock_delay.set(&delay);
+        Ok(())
+    }
+
+    default fn get_timelock_delay(&self) -> MilliSeconds {
+        self.data::<Data>().timelock_delay.get().unwrap_or_default()
+    }
+
+    default fn _check_timelock(&mut self, operation: TimelockOperation) -> Result<(), PSP34Error> {
+        if self.get_timelock_delay() == 0 {
+            return Ok(());
+        }
+
+        let operation_hash = self.operation_hash(&operation);
+        match self.data::<Data>().timelock_operations.get(operation_hash) {
+            Some(scheduled) if scheduled.ready_at <= Self::env().block_timestamp() => {
+                self.remove_operation(operation_hash);
+                self._emit_operation_executed_event(scheduled.id);
+                Ok(())
+            }
+            Some(_) => Err(PSP34Error::Custom(String::from(
+                Shiden34Error::OperationNotReady.as_str(),
+            ))),
+            None => Err(PSP34Error::Custom(String::from(
+                Shiden34Error::OperationNotScheduled.as_str(),
+            ))),
+        }
+    }
+
+    default fn _emit_operation_scheduled_event(
+        &self,
+        _operation_id: u32,
+        _operation: TimelockOperation,
+        _proposer: AccountId,
+        _ready_at: BlockTimestamp,
+    ) {
+    }
+
+    default fn _emit_operation_executed_event(&self, _operation_id: u32) {}
+
+    default fn _emit_operation_cancelled_event(&self, _operation_id: u32) {}
+}
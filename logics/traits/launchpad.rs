@@ -1,10 +1,13 @@
-use ink::prelude::vec::Vec;
+use ink::prelude::{string::String as PreludeString, vec::Vec};
 use openbrush::{
     contracts::psp34::{Id, PSP34Error},
     traits::{AccountId, Balance, String},
 };
 
-use crate::impls::launchpad::types::{MilliSeconds, Percentage};
+use crate::impls::launchpad::types::{
+    AddressKindIndex, BasisPoints, CollectionAttributeIndex, DefaultProposal, FinancialSummary,
+    MilliSeconds, Percentage,
+};
 
 #[openbrush::wrapper]
 pub type LaunchpadRef = dyn Launchpad;
@@ -26,6 +29,22 @@ pub trait Launchpad {
     #[ink(message)]
     fn get_refund_amount(&self, token_id: u64) -> Balance;
 
+    /// Get refund amount for given token_id if it were refunded at the given timestamp
+    #[ink(message)]
+    fn get_refund_amount_at(&self, token_id: u64, timestamp: u64) -> Balance;
+
+    /// Get every remaining (deadline_timestamp, refund_amount) of the given token_id
+    #[ink(message)]
+    fn get_refund_schedule(&self, token_id: u64) -> Vec<(u64, Balance)>;
+
+    /// Get the amount owed to holders if every live token were refunded at the current period
+    #[ink(message)]
+    fn get_refund_liability(&self) -> Balance;
+
+    /// Get the contract balance not needed to honour outstanding refunds
+    #[ink(message)]
+    fn get_excess_balance(&self) -> Balance;
+
     #[ink(message)]
     fn get_available_to_withdraw_launchpad(&self) -> Balance;
 
@@ -40,6 +59,51 @@ pub trait Launchpad {
     #[ink(message)]
     fn withdraw_project(&mut self) -> Result<(), PSP34Error>;
 
+    /// Withdraw part of the launchpad funds to the given destination
+    #[ink(message)]
+    fn withdraw_launchpad_to(
+        &mut self,
+        destination: AccountId,
+        amount: Balance,
+    ) -> Result<(), PSP34Error>;
+
+    /// Withdraw part of the caller's project funds to the given destination
+    #[ink(message)]
+    fn withdraw_project_to(
+        &mut self,
+        destination: AccountId,
+        amount: Balance,
+    ) -> Result<(), PSP34Error>;
+
+    /// Get sales, refunds, withdrawals and balances of the contract in a single call
+    #[ink(message)]
+    fn get_financial_summary(&self) -> FinancialSummary;
+
+    /// Get the amount the given payee can withdraw now
+    #[ink(message)]
+    fn get_available_to_withdraw_payee(&self, account_id: AccountId) -> Balance;
+
+    /// Get the total amount the given payee withdrew so far
+    #[ink(message)]
+    fn get_withdrawn_sales(&self, account_id: AccountId) -> Balance;
+
+    /// Split the project share between payees, shares are in basis points summing to 10000
+    #[ink(message)]
+    fn set_project_payees(
+        &mut self,
+        payees: Vec<(AccountId, BasisPoints)>,
+    ) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn get_project_payees(&self) -> Vec<(AccountId, BasisPoints)>;
+
+    /// Send the caller's pending credit from failed refunds or withdrawals to the given account
+    #[ink(message)]
+    fn claim(&mut self, to: AccountId) -> Result<Balance, PSP34Error>;
+
+    #[ink(message)]
+    fn get_pending_credit(&self, account_id: AccountId) -> Balance;
+
     /// Set max number of tokens which could be minted per call
     #[ink(message)]
     fn set_max_mint_amount(&mut self, max_amount: u64) -> Result<(), PSP34Error>;
@@ -78,6 +142,65 @@ pub trait Launchpad {
     #[ink(message)]
     fn get_refund_shares(&self) -> Vec<Percentage>;
 
+    /// Set the URI of the collection-level metadata, an empty URI removes it
+    #[ink(message)]
+    fn set_contract_uri(&mut self, uri: PreludeString) -> Result<(), PSP34Error>;
+
+    /// Get the URI of the collection-level metadata (description, image, links) read by marketplaces
+    #[ink(message)]
+    fn contract_uri(&self) -> Option<PreludeString>;
+
+    /// Set 0 description, 1 image, 2 banner, 3 external URL, 4 twitter or 5 discord of the collection,
+    /// an empty value removes it
+    #[ink(message)]
+    fn set_collection_attribute(
+        &mut self,
+        attribute_index: CollectionAttributeIndex,
+        value: PreludeString,
+    ) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn get_collection_attribute(
+        &self,
+        attribute_index: CollectionAttributeIndex,
+    ) -> Option<PreludeString>;
+
+    /// Block transfers of tokens while they are refundable, mints, burns and refunds stay allowed.
+    /// Can't be changed after the first sale
+    #[ink(message)]
+    fn set_transfer_lock(&mut self, enabled: bool) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn get_transfer_lock(&self) -> bool;
+
+    /// Whether the token can currently be transferred, for marketplaces to check before listing
+    #[ink(message)]
+    fn is_transferable(&self, token_id: u64) -> bool;
+
+    /// Give up the refund of an owned token, making it transferable
+    #[ink(message)]
+    fn waive_refund(&mut self, token_id: u64) -> Result<(), PSP34Error>;
+
+    /// Transfer a token resold on a marketplace, recording the price as its last sale price
+    #[ink(message)]
+    fn transfer_sold(
+        &mut self,
+        to: AccountId,
+        token_id: u64,
+        price: Balance,
+    ) -> Result<(), PSP34Error>;
+
+    /// Check the transfer lock, called from the psp34 transfer hook
+    fn _check_transfer_allowed(
+        &self,
+        from: Option<&AccountId>,
+        to: Option<&AccountId>,
+        id: &Id,
+    ) -> Result<(), PSP34Error>;
+
+    /// Update the last sale price of a resold token, called from the psp34 transfer hook
+    fn _record_transfer_price(&mut self, from: Option<&AccountId>, to: Option<&AccountId>, id: &Id);
+
     #[ink(message)]
     fn get_refund_address(&self) -> AccountId;
 
@@ -90,9 +213,14 @@ pub trait Launchpad {
     #[ink(message)]
     fn get_launchpad_treasury_address(&self) -> AccountId;
 
+    /// Once a token is sold refund periods can only be extended
     #[ink(message)]
     fn set_refund_periods(&mut self, refund_periods: Vec<MilliSeconds>) -> Result<(), PSP34Error>;
 
+    /// Once a token is sold refund shares can only be increased
+    #[ink(message)]
+    fn set_refund_shares(&mut self, refund_shares: Vec<Percentage>) -> Result<(), PSP34Error>;
+
     /// Get max number of tokens which could be minted per call
     #[ink(message)]
     fn get_max_mint_amount(&mut self) -> u64;
@@ -135,6 +263,120 @@ pub trait Launchpad {
     #[ink(message)]
     fn get_minting_status(&self) -> String;
 
+    /// Set who is allowed to refund a token before the first sale: 0 any holder, 1 original minter only, 2 any holder capped at the last sale price
+    #[ink(message)]
+    fn set_refund_policy(&mut self, refund_policy_index: u8) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn get_refund_policy(&self) -> u8;
+
+    #[ink(message)]
+    fn get_original_minter(&self, token_id: u64) -> Option<AccountId>;
+
+    /// Get the price the token was last sold for, used to cap refunds of secondary holders:
+    /// the mint price, the resale price reported by a marketplace, or 0 after an unpaid transfer
+    #[ink(message)]
+    fn get_last_sale_price(&self, token_id: u64) -> Balance;
+
+    /// Set quorum and approval threshold (percentages of the escrowed sales) and voting period of default proposals.
+    /// Can't be changed after the first sale
+    #[ink(message)]
+    fn set_default_vote_config(
+        &mut self,
+        quorum: Percentage,
+        threshold: Percentage,
+        voting_period: MilliSeconds,
+    ) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn get_default_vote_config(&self) -> (Percentage, Percentage, MilliSeconds);
+
+    /// Open a vote to declare the project in default
+    #[ink(message)]
+    fn propose_default(&mut self) -> Result<u32, PSP34Error>;
+
+    /// Vote on the open default proposal with the given tokens, weighted by their escrowed price
+    #[ink(message)]
+    fn vote_default(&mut self, token_ids: Vec<u64>, support: bool) -> Result<(), PSP34Error>;
+
+    /// Close the default proposal once its voting window ended, returns whether the project defaulted
+    #[ink(message)]
+    fn finalize_default(&mut self) -> Result<bool, PSP34Error>;
+
+    #[ink(message)]
+    fn get_default_proposal(&self) -> Option<DefaultProposal>;
+
+    #[ink(message)]
+    fn is_project_defaulted(&self) -> bool;
+
+    /// Split the project share into milestones (percentages summing to 100), empty to release it without approval
+    #[ink(message)]
+    fn set_milestones(&mut self, milestone_shares: Vec<Percentage>) -> Result<(), PSP34Error>;
+
+    /// Set the account allowed to approve milestones besides the launchpad treasury
+    #[ink(message)]
+    fn set_milestone_arbiter(&mut self, arbiter: Option<AccountId>) -> Result<(), PSP34Error>;
+
+    /// Unlock the tranche of the project share tied to the given milestone
+    #[ink(message)]
+    fn approve_milestone(&mut self, index: u32) -> Result<(), PSP34Error>;
+
+    /// Declare the project failed, unreleased tranches become refundable to holders
+    #[ink(message)]
+    fn declare_project_failed(&mut self) -> Result<(), PSP34Error>;
+
+    /// Get (share, approved) of every milestone
+    #[ink(message)]
+    fn get_milestones(&self) -> Vec<(Percentage, bool)>;
+
+    #[ink(message)]
+    fn get_milestone_arbiter(&self) -> Option<AccountId>;
+
+    /// Vest the project share linearly over `duration` after the public sale ends, nothing before `cliff`,
+    /// a zero duration disables vesting. Can only be changed before the first sale
+    #[ink(message)]
+    fn set_vesting_schedule(
+        &mut self,
+        cliff: MilliSeconds,
+        duration: MilliSeconds,
+    ) -> Result<(), PSP34Error>;
+
+    /// Get (cliff, duration) of the project vesting schedule
+    #[ink(message)]
+    fn get_vesting_schedule(&self) -> (MilliSeconds, MilliSeconds);
+
+    /// Get the project share vested so far, including what was already withdrawn
+    #[ink(message)]
+    fn get_vested_project_share(&self) -> Balance;
+
+    /// Get the project share withdrawn so far by all payees
+    #[ink(message)]
+    fn get_withdrawn_project_share(&self) -> Balance;
+
+    /// Get the project share released by the refund periods and milestones but not vested yet
+    #[ink(message)]
+    fn get_locked_project_share(&self) -> Balance;
+
+    /// Propose a new address for 0 project treasury, 1 launchpad treasury or 2 refund address,
+    /// callable by the current holder, or the owner except for the launchpad treasury
+    #[ink(message)]
+    fn propose_address_change(
+        &mut self,
+        address_kind_index: AddressKindIndex,
+        new_address: AccountId,
+    ) -> Result<(), PSP34Error>;
+
+    /// Accept the pending address change, callable by the proposed address
+    #[ink(message)]
+    fn accept_address_change(
+        &mut self,
+        address_kind_index: AddressKindIndex,
+    ) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn get_pending_address_change(&self, address_kind_index: AddressKindIndex)
+        -> Option<AccountId>;
+
     fn _emit_refund_event(
         &self,
         from: AccountId,
@@ -143,4 +385,26 @@ pub trait Launchpad {
         price: Balance,
         refunded: Balance,
     );
+
+    fn _emit_project_defaulted_event(&self, proposal_id: Option<u32>, refund_ratio: u128);
+
+    fn _emit_withdrawn_event(&self, payer: AccountId, destination: AccountId, amount: Balance);
+
+    fn _emit_address_change_proposed_event(
+        &self,
+        address_kind_index: AddressKindIndex,
+        proposer: AccountId,
+        new_address: AccountId,
+    );
+
+    fn _emit_address_changed_event(
+        &self,
+        address_kind_index: AddressKindIndex,
+        old_address: Option<AccountId>,
+        new_address: AccountId,
+    );
+
+    fn _emit_refund_waived_event(&self, holder: AccountId, token_id: u64);
+
+    fn _emit_milestone_approved_event(&self, index: u32, approver: AccountId);
 }
@@ -3,6 +3,8 @@ use ink::prelude::vec::Vec;
 
 use openbrush::contracts::psp34::{Id, PSP34Error};
 
+use crate::impls::launchpad::types::{IdFormatIndex, TokenId};
+
 #[openbrush::wrapper]
 pub type Psp34TraitsRef = dyn Psp34Traits;
 
@@ -19,10 +21,38 @@ pub trait Psp34Traits {
         metadata: Vec<(PreludeString, PreludeString)>,
     ) -> Result<(), PSP34Error>;
 
+    /// Set attributes of many tokens at once, returns the (token_id, error) of every item that failed
+    #[ink(message)]
+    fn set_attributes_batch(
+        &mut self,
+        items: Vec<(Id, Vec<(PreludeString, PreludeString)>)>,
+    ) -> Result<Vec<(Id, PSP34Error)>, PSP34Error>;
+
+    /// Remove attributes from a token, dropping attribute names no token uses anymore
+    #[ink(message)]
+    fn remove_attributes(
+        &mut self,
+        token_id: Id,
+        attributes: Vec<PreludeString>,
+    ) -> Result<(), PSP34Error>;
+
     /// This function returns all available attributes of each NFT
     #[ink(message)]
     fn get_attributes(&self, token_id: Id, attributes: Vec<PreludeString>) -> Vec<PreludeString>;
 
+    /// Get the (name, value) of every attribute set on the token
+    #[ink(message)]
+    fn get_all_attributes(&self, token_id: Id) -> Vec<(PreludeString, PreludeString)>;
+
+    /// Get the (name, value) of the attributes set on the token among `limit` attribute names, skipping the first `from` names
+    #[ink(message)]
+    fn get_attributes_page(
+        &self,
+        token_id: Id,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(PreludeString, PreludeString)>;
+
     /// This function return how many unique attributes in the contract
     #[ink(message)]
     fn get_attribute_count(&self) -> u32;
@@ -34,4 +64,74 @@ pub trait Psp34Traits {
     /// Get URI from token ID
     #[ink(message)]
     fn token_uri(&self, token_id: u64) -> PreludeString;
+
+    /// Get URI from token ID, returns TokenNotExists for tokens which are not minted
+    #[ink(message)]
+    fn get_token_uri(&self, token_id: u64) -> Result<PreludeString, PSP34Error>;
+
+    /// Set the suffix (such as ".json" or none), the zero padding and the 0 decimal or 1 hex
+    /// format of the token ID appended to the baseUri
+    #[ink(message)]
+    fn set_uri_format(
+        &mut self,
+        suffix: PreludeString,
+        padding: u8,
+        id_format_index: IdFormatIndex,
+    ) -> Result<(), PSP34Error>;
+
+    /// Get the (suffix, padding, id format) of token URIs
+    #[ink(message)]
+    fn get_uri_format(&self) -> (PreludeString, u8, IdFormatIndex);
+
+    /// Set a URI for the given token which takes precedence over the baseUri
+    #[ink(message)]
+    fn set_token_uri(&mut self, token_id: TokenId, uri: PreludeString) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn set_token_uri_batch(
+        &mut self,
+        token_uris: Vec<(TokenId, PreludeString)>,
+    ) -> Result<(), PSP34Error>;
+
+    /// Make token_uri return a base64 JSON document built on-chain from the token attributes
+    #[ink(message)]
+    fn set_on_chain_metadata(&mut self, enabled: bool) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn is_on_chain_metadata(&self) -> bool;
+
+    /// Set the name and description used for tokens without their own, `{id}` is replaced by the token ID
+    #[ink(message)]
+    fn set_metadata_template(
+        &mut self,
+        name: PreludeString,
+        description: PreludeString,
+    ) -> Result<(), PSP34Error>;
+
+    /// Get the (name, description) template
+    #[ink(message)]
+    fn get_metadata_template(&self) -> (PreludeString, PreludeString);
+
+    /// Remove the URI override of the given token, falling back to the baseUri
+    #[ink(message)]
+    fn remove_token_uri(&mut self, token_id: TokenId) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn remove_token_uri_batch(&mut self, token_ids: Vec<TokenId>) -> Result<(), PSP34Error>;
+
+    /// Irreversibly freeze the base URI and the attributes of every token
+    #[ink(message)]
+    fn freeze_metadata(&mut self) -> Result<(), PSP34Error>;
+
+    /// Irreversibly freeze the attributes of the given token
+    #[ink(message)]
+    fn freeze_token_metadata(&mut self, token_id: TokenId) -> Result<(), PSP34Error>;
+
+    /// Whether the metadata of the given token can no longer change
+    #[ink(message)]
+    fn is_metadata_frozen(&self, token_id: TokenId) -> bool;
+
+    fn _emit_metadata_frozen_event(&self, token_id: Option<TokenId>);
+
+    fn _emit_attributes_batch_set_event(&self, updated: u32, failed: u32);
 }
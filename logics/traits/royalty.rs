@@ -0,0 +1,37 @@
+use openbrush::{
+    contracts::psp34::PSP34Error,
+    traits::{AccountId, Balance},
+};
+
+use crate::impls::launchpad::types::BasisPoints;
+
+#[openbrush::wrapper]
+pub type RoyaltyRef = dyn Royalty;
+
+#[openbrush::trait_definition]
+pub trait Royalty {
+    /// Get the receiver and royalty amount owed for a secondary sale of the token at the given price
+    #[ink(message)]
+    fn royalty_info(&self, token_id: u64, sale_price: Balance) -> Option<(AccountId, Balance)>;
+
+    /// Set the royalty applied to every token without an override, None removes it
+    #[ink(message)]
+    fn set_default_royalty(
+        &mut self,
+        royalty: Option<(AccountId, BasisPoints)>,
+    ) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn get_default_royalty(&self) -> Option<(AccountId, BasisPoints)>;
+
+    /// Override the collection royalty for a single token, None falls back to the collection royalty
+    #[ink(message)]
+    fn set_token_royalty(
+        &mut self,
+        token_id: u64,
+        royalty: Option<(AccountId, BasisPoints)>,
+    ) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn get_token_royalty(&self, token_id: u64) -> Option<(AccountId, BasisPoints)>;
+}
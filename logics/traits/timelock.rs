@@ -0,0 +1,46 @@
+use ink::prelude::vec::Vec;
+use openbrush::{contracts::psp34::PSP34Error, traits::AccountId};
+
+use crate::impls::launchpad::types::{
+    BlockTimestamp, MilliSeconds, ScheduledOperation, TimelockOperation,
+};
+
+#[openbrush::wrapper]
+pub type TimelockRef = dyn Timelock;
+
+#[openbrush::trait_definition]
+pub trait Timelock {
+    /// Schedule an admin action, it is executed by calling the original message with the same arguments once the delay passed.
+    /// An identical action can't be pending twice, and at most `MAX_PENDING_OPERATIONS` can be pending at once
+    #[ink(message)]
+    fn schedule_operation(&mut self, operation: TimelockOperation) -> Result<u32, PSP34Error>;
+
+    /// Cancel a scheduled operation, callable by its proposer or the owner
+    #[ink(message)]
+    fn cancel_operation(&mut self, operation_id: u32) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn get_pending_operations(&self) -> Vec<ScheduledOperation>;
+
+    /// Set the delay admin actions have to wait, a shorter delay has to be scheduled itself
+    #[ink(message)]
+    fn set_timelock_delay(&mut self, delay: MilliSeconds) -> Result<(), PSP34Error>;
+
+    #[ink(message)]
+    fn get_timelock_delay(&self) -> MilliSeconds;
+
+    /// Consume the ready scheduled operation matching the action, passes when no delay is set
+    fn _check_timelock(&mut self, operation: TimelockOperation) -> Result<(), PSP34Error>;
+
+    fn _emit_operation_scheduled_event(
+        &self,
+        operation_id: u32,
+        operation: TimelockOperation,
+        proposer: AccountId,
+        ready_at: BlockTimestamp,
+    );
+
+    fn _emit_operation_executed_event(&self, operation_id: u32);
+
+    fn _emit_operation_cancelled_event(&self, operation_id: u32);
+}